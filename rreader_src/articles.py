@@ -0,0 +1,105 @@
+import hashlib
+import os
+import urllib.parse
+from html.parser import HTMLParser
+
+from .common import p, readJSON, writeJSON
+from .config import SETTINGS
+from .net import fetch, rewrite, text
+
+# Text of the pages behind the newest "prefetch" entries of each category, saved on refresh
+# in the articles folder of the cache dir, so the preview and [I] work without a connection
+
+
+class ArticleParser(HTMLParser):
+
+    BLOCKS = ("p", "h1", "h2", "h3", "h4", "h5", "h6", "li", "blockquote", "pre")
+    SKIP = ("script", "style", "nav", "header", "footer", "aside", "form", "noscript")
+
+    def __init__(self):
+        super().__init__()
+        self.paragraphs, self.article, self.links = [], [], []
+        self.current = None
+        self.skip = self.inArticle = 0
+
+    def handle_starttag(self, tag, attrs):
+        href = dict(attrs).get("href") if tag == "a" and self.current is not None and not self.skip else None
+        if href and href not in self.links:
+            self.links.append(href)
+
+        if tag in self.SKIP:
+            self.skip += 1
+        elif tag == "article":
+            self.inArticle += 1
+        elif tag in self.BLOCKS and self.current is None:
+            self.current = []
+
+    def handle_endtag(self, tag):
+        if tag in self.SKIP:
+            self.skip = max(self.skip - 1, 0)
+        elif tag == "article":
+            self.inArticle = max(self.inArticle - 1, 0)
+        elif tag in self.BLOCKS and self.current is not None:
+            paragraph = " ".join("".join(self.current).split())
+            if paragraph:
+                self.paragraphs.append(paragraph)
+                if self.inArticle:
+                    self.article.append(paragraph)
+            self.current = None
+
+    def handle_data(self, data):
+        if self.current is not None and not self.skip:
+            self.current.append(data)
+
+
+def extract(html, base=None):
+
+    # Paragraphs inside <article> when the page has one, else every paragraph of the page.
+    # With base, also the links found in paragraphs, made absolute
+
+    parser = ArticleParser()
+    parser.feed(html)
+
+    text = "\n".join(parser.article or parser.paragraphs)
+
+    if base is None:
+        return text
+
+    links = [urllib.parse.urljoin(base, href) for href in parser.links]
+
+    return text, [link for link in links if link.startswith(("http://", "https://")) and link != base]
+
+
+def path(entry):
+    return os.path.join(p["path_cache"], "articles", hashlib.sha1(str(entry["id"]).encode("utf-8")).hexdigest() + ".json")
+
+
+def read(entry):
+    return (readJSON(path(entry)) or {}).get("text")
+
+
+def links(entry):
+    return (readJSON(path(entry)) or {}).get("links", [])
+
+
+def save(entry):
+
+    # Fetches the page again, also when it was saved before
+
+    body, headers = fetch(rewrite(entry["url"]))
+    article, found = extract(text(body, headers), entry["url"])
+
+    os.makedirs(os.path.join(p["path_cache"], "articles"), exist_ok=True)
+    writeJSON(path(entry), {"url": entry["url"], "text": article, "links": found})
+
+
+def prefetch(entries):
+
+    for entry in entries[: SETTINGS.get("prefetch", 0)]:
+        if not entry.get("url") or os.path.isfile(path(entry)):
+            continue
+
+        try:
+            save(entry)
+        except:
+            continue
@@ -0,0 +1,114 @@
+import os
+import sqlite3
+import sys
+import time
+
+from .articles import path as articlePath
+from .common import loadJSON, p, readJSON, writeJSON
+from .config import SETTINGS
+
+# "cacheMaxEntries" per category and "cacheMaxAge" in days, from config.json
+
+
+def prune(entries):
+
+    # entries are sorted newest first
+
+    if SETTINGS.get("cacheMaxAge"):
+        oldest = time.time() - SETTINGS["cacheMaxAge"] * 86400
+        entries = [entry for entry in entries if entry.get("timestamp", 0) >= oldest]
+
+    return entries[: SETTINGS.get("cacheMaxEntries", 1000)]
+
+
+def entries():
+
+    # Every cached entry of every category, for matching against a sync service
+
+    for name in sorted(os.listdir(p["path_cache"])) if os.path.isdir(p["path_cache"]) else []:
+        if name.startswith("rss_") and name.endswith(".json"):
+            yield from (readJSON(os.path.join(p["path_cache"], name)) or {}).get("entries", [])
+
+
+def clean(log=True):
+
+    # rr cache clean: drops caches of categories no longer in feeds.json, prunes the others and the search index
+
+    categories = loadJSON(p["file_feeds"])
+
+    removed = pruned = 0
+
+    for name in sorted(os.listdir(p["path_cache"])):
+        path = os.path.join(p["path_cache"], name)
+
+        if name.endswith(".broken"):
+            os.remove(path)
+            removed += 1
+            continue
+
+        if not (name.startswith("rss_") and name.endswith(".json")):
+            continue
+
+        if name[4:-5] not in categories:
+            os.remove(path)
+            removed += 1
+            continue
+
+        d = readJSON(path)
+
+        if d is None:
+            removed += 1
+            continue
+
+        entries = prune(d["entries"])
+        pruned += len(d["entries"]) - len(entries)
+        d["entries"] = entries
+
+        writeJSON(path, d)
+
+    # Saved articles of entries no longer cached
+
+    kept = set()
+
+    for name in os.listdir(p["path_cache"]):
+        if name.startswith("rss_") and name.endswith(".json"):
+            kept.update(articlePath(entry) for entry in (readJSON(os.path.join(p["path_cache"], name)) or {"entries": []})["entries"])
+
+    if os.path.isdir(os.path.join(p["path_cache"], "articles")):
+        for name in os.listdir(os.path.join(p["path_cache"], "articles")):
+            if os.path.join(p["path_cache"], "articles", name) not in kept:
+                os.remove(os.path.join(p["path_cache"], "articles", name))
+                removed += 1
+
+    indexed = 0
+
+    if os.path.isfile(os.path.join(p["path_data"], "index.db")) and SETTINGS.get("cacheMaxAge"):
+        db = sqlite3.connect(os.path.join(p["path_data"], "index.db"))
+        oldest = time.time() - SETTINGS["cacheMaxAge"] * 86400
+        with db:
+            rowids = [row[0] for row in db.execute("SELECT rowid FROM entries WHERE timestamp < ?", (oldest,))]
+            db.executemany("DELETE FROM entries WHERE rowid = ?", [(d,) for d in rowids])
+            try:
+                db.executemany("DELETE FROM fts WHERE rowid = ?", [(d,) for d in rowids])
+            except sqlite3.OperationalError:
+                pass
+        db.execute("VACUUM")
+        db.close()
+        indexed = len(rowids)
+
+    if log:
+        sys.stdout.write(f"Removed {removed} caches, {pruned} cached entries and {indexed} indexed entries\n")
+
+
+def rebuild():
+
+    # rr cache rebuild: throws away fetched feeds and found feed addresses, then fetches everything again.
+    # The search index is kept, as it holds entries no longer in the feeds
+
+    for name in os.listdir(p["path_cache"]):
+        if name.startswith("rss_") or name.startswith("discovered.json"):
+            os.remove(os.path.join(p["path_cache"], name))
+
+    from .get_rss import do as getFeedFromRSS
+
+    getFeedFromRSS(log=True)
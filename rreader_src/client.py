@@ -0,0 +1,25 @@
+import json
+import urllib.parse
+import urllib.request
+
+from .config import SETTINGS
+
+# With "server" in config.json, the reader gets feeds and read state from a running rr serve
+# instead of fetching them itself, so every session shares one cache and one refresh schedule
+
+
+def server():
+    return SETTINGS.get("server", "").rstrip("/")
+
+
+def getFeed(category):
+
+    with urllib.request.urlopen("%s/%s.json" % (server(), urllib.parse.quote(category)), timeout=30) as r:
+        return json.loads(r.read().decode("utf-8"))
+
+
+def markRead(id):
+
+    req = urllib.request.Request(server() + "/read", data=json.dumps([id]).encode("utf-8"), headers={"Content-Type": "application/json"})
+
+    urllib.request.urlopen(req, timeout=10).close()
@@ -0,0 +1,34 @@
+import base64
+import os
+import shutil
+import subprocess
+import sys
+
+COMMANDS = [
+    ["pbcopy"],
+    ["wl-copy"],
+    ["xclip", "-selection", "clipboard"],
+    ["xsel", "--clipboard", "--input"],
+    ["clip"],
+]
+
+
+def copy(text):
+
+    # System clipboard tools first; over SSH, or when none is available, ask the terminal via OSC 52
+
+    if not os.environ.get("SSH_CONNECTION"):
+        for command in COMMANDS:
+            if command[0] == "wl-copy" and not os.environ.get("WAYLAND_DISPLAY"):
+                continue
+            if shutil.which(command[0]):
+                try:
+                    subprocess.run(command, input=text.encode("utf-8"), check=True, timeout=2)
+                    return True
+                except (OSError, subprocess.SubprocessError):
+                    continue
+
+    sys.stdout.write("\033]52;c;%s\a" % base64.b64encode(text.encode("utf-8")).decode("ascii"))
+    sys.stdout.flush()
+
+    return True
@@ -0,0 +1,87 @@
+import os
+
+# asciimatics draws with palette indices, so RGB theme colors are mapped to the closest one,
+# and truecolor terminals get the 256-color palette
+
+CUBE = [0, 95, 135, 175, 215, 255]
+
+BASIC = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+]
+
+
+TRUECOLOR = 1 << 24
+
+
+def detect(name=None):
+
+    # Color depth from --colors, then $COLORTERM, then the terminal's terminfo entry, then $TERM
+
+    if name:
+        return {"16": 16, "256": 256, "truecolor": TRUECOLOR}[name]
+
+    if os.environ.get("COLORTERM", "").lower() in ("truecolor", "24bit"):
+        return TRUECOLOR
+
+    try:
+        import curses
+
+        curses.setupterm()
+
+        if curses.tigetflag("RGB") > 0 or curses.tigetnum("colors") >= TRUECOLOR:
+            return TRUECOLOR
+
+        if curses.tigetnum("colors") >= 256:
+            return 256
+    except:
+        pass
+
+    return 256 if "256" in os.environ.get("TERM", "") else 16
+
+
+def palette(depth):
+
+    if depth < 256:
+        return BASIC
+
+    cube = [(r, g, b) for r in CUBE for g in CUBE for b in CUBE]
+    gray = [(8 + 10 * i,) * 3 for i in range(24)]
+
+    return BASIC + cube + gray
+
+
+def toIndex(value, depth=256):
+
+    # Accepts a palette index or an "#rrggbb" or "#rgb" string; anything else raises ValueError
+
+    if isinstance(value, int) and not isinstance(value, bool) and 0 <= value < 256:
+        return value
+
+    if not isinstance(value, str) or not value.startswith("#") or len(value) not in (4, 7):
+        raise ValueError(f"not a color: {value!r}")
+
+    value = value[1:]
+
+    if len(value) == 3:
+        value = "".join(d * 2 for d in value)
+
+    rgb = tuple(int(value[i : i + 2], 16) for i in (0, 2, 4))
+
+    colors = palette(depth)
+
+    return min(range(len(colors)), key=lambda i: sum((a - b) ** 2 for a, b in zip(colors[i], rgb)))
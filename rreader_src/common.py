@@ -1,14 +1,112 @@
 from pathlib import Path
+import json
 import os
+import shutil
+import sys
+import tempfile
 
 defaultdir = str(Path.home()) + "/"
 
-p = {"pathkeys": ["path_data"], "path_data": defaultdir + ".rreader/"}
 
+def xdgDir(env, fallback):
+    return os.path.join(os.environ.get(env) or defaultdir + fallback, "rreader") + "/"
 
-FEEDS_FILE_NAME = os.path.join(p["path_data"], "feeds.json")
 
+# feeds.json and config.json go to the config dir, fetched feeds to the cache dir
 
-for d in p["pathkeys"]:
-    if not os.path.exists(p[d]):
-        os.mkdir(p[d])
+p = {"pathkeys": ["path_config", "path_data", "path_cache"]}
+
+LEGACY_PATH = defaultdir + ".rreader/"
+
+
+def setPaths(data_dir=None, config_file=None):
+
+    if data_dir:
+        for d in p["pathkeys"]:
+            p[d] = os.path.join(os.path.abspath(os.path.expanduser(data_dir)), "")
+    else:
+        p["path_config"] = xdgDir("XDG_CONFIG_HOME", ".config")
+        p["path_data"] = xdgDir("XDG_DATA_HOME", ".local/share")
+        p["path_cache"] = xdgDir("XDG_CACHE_HOME", ".cache")
+
+    p["file_feeds"] = os.path.join(p["path_config"], "feeds.json")
+    p["file_config"] = os.path.abspath(os.path.expanduser(config_file)) if config_file else os.path.join(p["path_config"], "config.json")
+
+    for d in p["pathkeys"]:
+        if not os.path.exists(p[d]):
+            os.makedirs(p[d])
+
+
+def migrateLegacyFiles():
+
+    # Move files over from ~/.rreader used by earlier versions
+
+    if not os.path.isdir(LEGACY_PATH):
+        return
+
+    for name in os.listdir(LEGACY_PATH):
+        if name in ("feeds.json", "config.json"):
+            target = p["path_config"]
+        elif name.startswith("rss_") or name == "discovered.json":
+            target = p["path_cache"]
+        else:
+            target = p["path_data"]
+
+        if not os.path.exists(os.path.join(target, name)):
+            shutil.move(os.path.join(LEGACY_PATH, name), os.path.join(target, name))
+
+    try:
+        os.rmdir(LEGACY_PATH)
+    except OSError:
+        pass
+
+
+def writeJSON(path, data, indent=None):
+
+    # Written to a temporary file first and renamed over the old one, so a crash never leaves half a file
+
+    fd, temp = tempfile.mkstemp(dir=os.path.dirname(path), prefix=".", suffix=".tmp")
+
+    try:
+        with os.fdopen(fd, "w", encoding="utf-8") as fp:
+            fp.write(json.dumps(data, ensure_ascii=False, indent=indent))
+            fp.flush()
+            os.fsync(fp.fileno())
+        os.replace(temp, path)
+    except:
+        os.remove(temp)
+        raise
+
+
+# Damaged cache and state files, set aside as NAME.broken and reported once rReader starts
+
+BROKEN = []
+
+
+def readJSON(path, default=None):
+
+    # For files rReader can rebuild: default when missing or damaged
+
+    try:
+        with open(path, "r", encoding="utf-8") as fp:
+            return json.load(fp)
+    except FileNotFoundError:
+        return default
+    except ValueError as e:
+        os.replace(path, path + ".broken")
+        BROKEN.append(f"{os.path.basename(path)} was damaged ({e}) and has been set aside")
+        return default
+
+
+def loadJSON(path):
+
+    # For feeds.json and config.json, which only the user can fix
+
+    try:
+        with open(path, "r", encoding="utf-8") as fp:
+            return json.load(fp)
+    except ValueError as e:
+        sys.exit(f"Cannot read {path}: {e}")
+
+
+setPaths()
@@ -1,5 +1,94 @@
 import datetime
+import json
+import os
+import re
+
+from .common import loadJSON, p
 
 # KST Seoul UTC+9
 
-TIMEZONE = datetime.timezone(datetime.timedelta(hours=9))
+TIMEZONE = datetime.timezone(datetime.timedelta(hours=9), "KST")
+
+# User settings from config.json in the config dir
+
+SETTINGS = {}
+
+# Environment variables whose setting is not their plain camelCase
+
+NAMES = {"RREADER_CACHE_TTL": "cacheTTL"}
+
+
+def loadSettings():
+
+    SETTINGS.clear()
+
+    if os.path.isfile(p["file_config"]):
+        SETTINGS.update(loadJSON(p["file_config"]))
+
+    # RREADER_HOST_DELAY=0 overrides "hostDelay", and so on; values are read as JSON when they can be
+
+    for key, value in os.environ.items():
+        if key.startswith("RREADER_") and key not in ("RREADER_CONFIG", "RREADER_DATA_DIR"):
+            words = key[8:].lower().split("_")
+            name = NAMES.get(key, words[0] + "".join(word.capitalize() for word in words[1:]))
+
+            # A key config.json already has is matched whatever its case
+
+            name = next((d for d in SETTINGS if d.lower() == name.lower()), name)
+
+            try:
+                value = json.loads(value)
+            except ValueError:
+                pass
+            SETTINGS[name] = value
+
+
+def timezone():
+
+    # "timezone" in config.json: "local", "UTC", a name like "Europe/Paris" (Python 3.9+) or an offset like "+09:00"
+
+    name = SETTINGS.get("timezone")
+
+    if not name:
+        return TIMEZONE
+
+    if name == "local":
+        return datetime.datetime.now().astimezone().tzinfo
+
+    if name.upper() == "UTC":
+        return datetime.timezone.utc
+
+    m = re.match(r"^([+-])(\d\d):?(\d\d)$", name)
+
+    if m:
+        return datetime.timezone((-1 if m.group(1) == "-" else 1) * datetime.timedelta(hours=int(m.group(2)), minutes=int(m.group(3))), name)
+
+    try:
+        from zoneinfo import ZoneInfo
+
+        return ZoneInfo(name)
+    except Exception:
+        return TIMEZONE
+
+
+def formatTime(timestamp):
+
+    # Entry times are formatted when shown, with "timeFormatToday" or "timeFormatOlder" from config.json
+
+    tz = timezone()
+    at = datetime.datetime.fromtimestamp(timestamp, tz)
+
+    if at.date() == datetime.datetime.now(tz).date():
+        fmt = SETTINGS.get("timeFormatToday", "%H:%M")
+    else:
+        fmt = SETTINGS.get("timeFormatOlder", "%b %d, %H:%M")
+
+    # A zone set in config.json is named next to the time
+
+    if SETTINGS.get("timezone"):
+        fmt += " %Z"
+
+    return at.strftime(fmt)
+
+
+loadSettings()
@@ -0,0 +1,52 @@
+import os
+import urllib.parse
+from html.parser import HTMLParser
+
+from .common import p, readJSON, writeJSON
+from .net import fetch, text
+
+FEED_TYPES = ("application/rss+xml", "application/atom+xml", "application/rdf+xml", "application/feed+json")
+
+
+class FeedLinkParser(HTMLParser):
+    def __init__(self):
+        super().__init__()
+        self.links = []
+
+    def handle_starttag(self, tag, attrs):
+        if tag != "link":
+            return
+
+        attrs = dict(attrs)
+
+        if "alternate" in (attrs.get("rel") or "").lower().split() and (attrs.get("type") or "").lower() in FEED_TYPES and attrs.get("href"):
+            self.links.append(attrs["href"])
+
+
+def loadDiscovered():
+    return readJSON(os.path.join(p["path_cache"], "discovered.json"), {})
+
+
+def findFeedURL(url):
+
+    discovered = loadDiscovered()
+
+    if url in discovered:
+        return discovered[url]
+
+    try:
+        body, headers = fetch(url)
+    except:
+        return None
+
+    parser = FeedLinkParser()
+    parser.feed(text(body, headers))
+
+    if not parser.links:
+        return None
+
+    discovered[url] = urllib.parse.urljoin(url, parser.links[0])
+
+    writeJSON(os.path.join(p["path_cache"], "discovered.json"), discovered)
+
+    return discovered[url]
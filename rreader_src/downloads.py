@@ -0,0 +1,122 @@
+import os
+import queue
+import re
+import threading
+import urllib.parse
+import urllib.request
+
+from .config import SETTINGS
+from .net import USER_AGENT
+
+# Enclosures queued with [D], downloaded one at a time on a worker thread
+
+DOWNLOADS, QUEUE = [], queue.Queue()
+
+
+def fileName(url, title):
+
+    name = os.path.basename(urllib.parse.urlparse(url).path)
+
+    if not name:
+        name = re.sub(r"[^\w.-]+", "_", title).strip("_") or "download"
+
+    return name
+
+
+def freePath(path):
+
+    # Many podcast hosts name every episode audio.mp3, so later ones become audio-2.mp3 and so on
+
+    base, ext = os.path.splitext(path)
+    n = 1
+
+    while os.path.exists(path) or os.path.exists(path + ".part") or any(d["path"] == path for d in DOWNLOADS):
+        n += 1
+        path = "%s-%d%s" % (base, n, ext)
+
+    return path
+
+
+def download(d):
+
+    d["status"] = "downloading"
+
+    req = urllib.request.Request(d["url"], headers={"User-Agent": USER_AGENT})
+
+    with urllib.request.urlopen(req, timeout=30) as r:
+        d["size"] = int(r.headers.get("Content-Length") or 0)
+
+        with open(d["path"] + ".part", "wb") as fp:
+            while True:
+                chunk = r.read(65536)
+                if not chunk:
+                    break
+                fp.write(chunk)
+                d["done"] += len(chunk)
+
+    os.replace(d["path"] + ".part", d["path"])
+
+    d["status"] = "done"
+
+
+def worker(notify):
+    while True:
+        d = QUEUE.get()
+
+        try:
+            download(d)
+            notify("Downloaded %s" % os.path.basename(d["path"]))
+        except Exception as e:
+            d["status"], d["error"] = "failed", str(e)
+            notify("Download failed")
+
+
+def add(url, title="", notify=print):
+
+    # Returns False when the url is already in the list; failed downloads are retried
+
+    for d in DOWNLOADS:
+        if d["url"] == url:
+            if d["status"] != "failed":
+                return False
+            d.update({"done": 0, "status": "queued"})
+            QUEUE.put(d)
+            return True
+
+    path = os.path.expanduser(SETTINGS.get("downloadDir", "~/Downloads"))
+    os.makedirs(path, exist_ok=True)
+
+    d = {"url": url, "title": title, "path": freePath(os.path.join(path, fileName(url, title))), "size": 0, "done": 0, "status": "queued"}
+
+    if not DOWNLOADS:
+        threading.Thread(target=worker, args=(notify,), daemon=True).start()
+
+    DOWNLOADS.append(d)
+    QUEUE.put(d)
+
+    return True
+
+
+def progress(d):
+
+    if d["status"] != "downloading":
+        return d["status"].capitalize()
+
+    if d["size"]:
+        return "%d%%" % (d["done"] * 100 // d["size"])
+
+    return "%.1fMB" % (d["done"] / 1048576)
+
+
+def status():
+
+    # Short text for the status area while downloads are pending, or None
+
+    pending = [d for d in DOWNLOADS if d["status"] in ("queued", "downloading")]
+
+    if not pending:
+        return None
+
+    current = [d for d in pending if d["status"] == "downloading"]
+
+    return "Downloading %d %s" % (len(pending), progress(current[0]) if current else "")
@@ -0,0 +1,108 @@
+import csv
+import datetime
+import html
+import os
+import sys
+import time
+
+from .common import loadJSON, p
+from .config import SETTINGS, formatTime, timezone
+from .headlines import getFeed
+from .state import READ
+
+# Entries as CSV or TSV rows for spreadsheets, with times in ISO 8601
+
+FIELDS = ["category", "title", "source", "url", "timestamp", "time", "read"]
+
+
+def rows(entries, category=None):
+
+    for entry in entries:
+        if "group" in entry:  # source headers of the grouped layout
+            continue
+
+        yield [
+            entry.get("category", category),
+            entry.get("title", ""),
+            entry.get("sourceName", ""),
+            entry.get("url", ""),
+            entry.get("timestamp", 0),
+            datetime.datetime.fromtimestamp(entry.get("timestamp", 0), timezone()).isoformat(),
+            int(entry.get("id") in READ),
+        ]
+
+
+def writeHTML(fp, entries, category=None, group="source"):
+
+    # A page of headlines under each category, then each source or day, that can be mailed or published as it is
+
+    esc = html.escape
+    day = lambda entry: datetime.datetime.fromtimestamp(entry.get("timestamp", 0), timezone()).strftime("%A, %B %d, %Y")
+
+    fp.write('<!DOCTYPE html>\n<html>\n<head>\n<meta charset="utf-8">\n<title>rReader, %s</title>\n' % esc(time.strftime("%Y-%m-%d")))
+    fp.write("<style>body{font-family:sans-serif;max-width:50em;margin:auto;padding:1em}li{margin:.3em 0}small{color:#888}</style>\n</head>\n<body>\n")
+
+    titles = {key: d.get("title", key) for key, d in loadJSON(p["file_feeds"]).items()}
+    sections = {}
+
+    for entry in entries:
+        if "group" not in entry:
+            heading = entry.get("sourceName", "") if group == "source" else day(entry)
+            sections.setdefault(entry.get("category", category), {}).setdefault(heading, []).append(entry)
+
+    for name, headings in sections.items():
+        fp.write("<h1>%s</h1>\n" % esc(titles.get(name, name or "")))
+
+        for heading, items in headings.items():
+            fp.write("<h2>%s</h2>\n<ul>\n" % esc(heading))
+            for entry in items:
+                note = formatTime(entry.get("timestamp", 0)) if group == "source" else entry.get("sourceName", "")
+                fp.write('<li><a href="%s">%s</a> <small>%s</small></li>\n' % (esc(entry.get("url", "")), esc(entry.get("title", "")), esc(note)))
+            fp.write("</ul>\n")
+
+    fp.write("</body>\n</html>\n")
+
+
+def write(fp, entries, category=None, fmt="csv"):
+
+    if fmt == "html":
+        return writeHTML(fp, entries, category, SETTINGS.get("exportGroup", "source"))
+
+    writer = csv.writer(fp, dialect="excel-tab" if fmt == "tsv" else "excel")
+    writer.writerow(FIELDS)
+    writer.writerows(rows(entries, category))
+
+
+def toFile(entries, category, fmt=None):
+
+    # [X] in the reader: a file named after the category and time in "exportDir" (~/Downloads by default). Returns its path
+
+    fmt = fmt or SETTINGS.get("exportFormat", "csv")
+    path = os.path.expanduser(SETTINGS.get("exportDir", SETTINGS.get("downloadDir", "~/Downloads")))
+    os.makedirs(path, exist_ok=True)
+
+    path = os.path.join(path, "rreader-%s-%s.%s" % (category, time.strftime("%Y%m%d-%H%M%S"), fmt))
+
+    with open(path, "w", encoding="utf-8", newline="") as fp:
+        write(fp, entries, category, fmt)
+
+    return path
+
+
+def do(categories, fmt="csv", output=None, ttl=lambda category: 120):
+
+    # rr export: every entry of the categories, to output or the standard output
+
+    entries = [dict(entry, category=category) for category in categories for entry in getFeed(category, ttl(category))["entries"]]
+
+    if output:
+        with open(output, "w", encoding="utf-8", newline="") as fp:
+            write(fp, entries, fmt=fmt)
+        sys.stdout.write(f"Exported {len(entries)} entries to {output}\n")
+        return
+
+    try:
+        write(sys.stdout, entries, fmt=fmt)
+        sys.stdout.flush()
+    except BrokenPipeError:  # the reading end went away
+        os.dup2(os.open(os.devnull, os.O_WRONLY), sys.stdout.fileno())
@@ -0,0 +1,125 @@
+import json
+import os
+import sys
+
+from .cache import entries as cachedEntries
+from .common import p, readJSON, writeJSON
+from .config import SETTINGS
+from .net import fetch
+from .opml import categoryKey, mergeFeeds
+from .state import READ, mergeRead, saveState
+
+API = "https://api.feedbin.com/v2/"
+
+# Pages of 100 recent Feedbin entries compared with the cache on rr sync
+
+PAGES = 5
+
+# Subscriptions without a tag go to this category
+
+DEFAULT_CATEGORY = ("feedbin", "Feedbin")
+
+
+def get(path, d):
+
+    body, headers = fetch(API + path, auth=(d["username"], d["password"]))
+
+    return json.loads(body)
+
+
+def markReadThere(ids, d):
+
+    # At most 1000 entries a request; POST .../delete.json stands for DELETE unread_entries.json
+
+    for i in range(0, len(ids), 1000):
+        fetch(API + "unread_entries/delete.json", data=json.dumps({"unread_entries": ids[i : i + 1000]}).encode("utf-8"), headers={"Content-Type": "application/json"}, auth=(d["username"], d["password"]))
+
+
+def syncRead(d, log=True):
+
+    # Read state both ways, matched by link. Feedbin ids are kept in feedbin.json in the cache dir,
+    # so entries opened later are marked read there at once, with markRead
+
+    unread = set(get("unread_entries.json", d))
+
+    local = {}
+    for entry in cachedEntries():
+        local.setdefault(entry.get("url"), []).append(entry["id"])
+
+    known, pulled, pushed = {}, [], []
+
+    for page in range(1, PAGES + 1):
+        entries = get("entries.json?per_page=100&page=%d" % page, d)
+
+        for entry in entries:
+            ids = local.get(entry.get("url"), [])
+
+            for id in ids:
+                known[id] = entry["id"]
+
+            if entry["id"] not in unread:
+                pulled += ids
+            elif any(id in READ for id in ids):
+                pushed.append(entry["id"])
+
+        if len(entries) < 100:
+            break
+
+    markReadThere(pushed, d)
+
+    pulled = list(dict.fromkeys(id for id in pulled if id not in READ))
+    mergeRead(pulled)
+    saveState()
+
+    writeJSON(os.path.join(p["path_cache"], "feedbin.json"), known)
+
+    if log:
+        sys.stdout.write(f"Marked {len(pulled)} entries read here and {len(pushed)} in Feedbin\n")
+
+
+def markRead(id):
+
+    # An entry opened in rReader, marked read in Feedbin when rr sync has seen it there
+
+    d = SETTINGS.get("feedbin")
+    known = readJSON(os.path.join(p["path_cache"], "feedbin.json"), {})
+
+    if d and id in known:
+        markReadThere([known[id]], d)
+
+
+def do(log=True):
+
+    # "feedbin" in config.json: {"username": "...", "password": "..."}
+
+    d = SETTINGS.get("feedbin")
+
+    if not d or "username" not in d or "password" not in d:
+        sys.exit("Set feedbin username and password in config.json")
+
+    try:
+        subscriptions = get("subscriptions.json", d)
+        taggings = get("taggings.json", d)
+    except Exception as e:
+        sys.exit(f"Feedbin: {e}")
+
+    tags = {}
+    for tagging in taggings:
+        tags.setdefault(tagging["feed_id"], []).append(tagging["name"])
+
+    imported = {}
+
+    for subscription in subscriptions:
+        for name in tags.get(subscription["feed_id"], [DEFAULT_CATEGORY[1]]):
+            key = categoryKey(name) if name != DEFAULT_CATEGORY[1] else DEFAULT_CATEGORY[0]
+            category = imported.setdefault(key, {"title": name, "feeds": {}})
+            category["feeds"][subscription["title"] or subscription["feed_url"]] = subscription["feed_url"]
+
+    mergeFeeds(imported, log=log)
+
+    try:
+        syncRead(d, log=log)
+    except Exception as e:
+        sys.exit(f"Feedbin: {e}")
+
+    return imported
@@ -0,0 +1,38 @@
+# Fuzzy matching for the pickers: the letters of the query have to appear in order,
+# runs of consecutive letters and matches at word starts count more
+
+
+def score(query, text):
+
+    # None when text does not match; higher is better
+
+    lower = text.lower()
+    rslt, pos, last = 0, 0, -2
+
+    for d in query.lower():
+        if d == " ":
+            continue
+
+        i = lower.find(d, pos)
+
+        if i < 0:
+            return None
+
+        rslt += 1
+
+        if i == last + 1:
+            rslt += 5
+
+        if i == 0 or not lower[i - 1].isalnum():
+            rslt += 3
+
+        last, pos = i, i + 1
+
+    return rslt - len(text) / 1000
+
+
+def rank(query, items, key=lambda d: d):
+
+    scored = [(score(query, key(d)), n, d) for n, d in enumerate(items)]
+
+    return [d for s, n, d in sorted([d for d in scored if d[0] is not None], key=lambda d: (-d[0], d[1]))]
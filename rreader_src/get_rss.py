@@ -1,75 +1,352 @@
-import datetime
+import calendar
 import feedparser
-import json
+import hashlib
 import os
+import random
+import re
 import shutil
 import sys
+import threading
 import time
+import urllib.parse
+import urllib.request
+from html.parser import HTMLParser
 
-from .common import p, FEEDS_FILE_NAME
-from .config import TIMEZONE
+from .articles import prefetch
+from .cache import prune
+from .common import loadJSON, p, readJSON, writeJSON
+from .config import SETTINGS, formatTime
+from .discover import findFeedURL
+from .hn import isHN, parse as parseHN
+from .hooks import runCommands, transform
+from .net import wait
+from .search import add as addToIndex
+from .reddit import isReddit, stats as redditStats, fields as redditFields
+from .youtube import feedURL as youtubeFeedURL
 
 
-def do(target_category=None, log=False):
-    def getFeedFromRSS(category, urls, show_author=False, log=False):
+def matchesAny(text, patterns):
 
-        rslt = {}
+    # Patterns are case-insensitive keywords, or regular expressions when written as /.../
+
+    for pattern in patterns:
+        if len(pattern) > 2 and pattern.startswith("/") and pattern.endswith("/"):
+            try:
+                if re.search(pattern[1:-1], text, re.IGNORECASE):
+                    return True
+            except re.error:
+                continue
+        elif pattern.lower() in text.lower():
+            return True
+
+    return False
+
+
+class TextParser(HTMLParser):
+
+    BLOCKS = ("p", "div", "br", "li", "h1", "h2", "h3", "h4", "h5", "h6", "blockquote", "pre", "tr")
+
+    def __init__(self):
+        super().__init__()
+        self.text, self.links = [], []
+        self.skip = 0
+
+    def handle_starttag(self, tag, attrs):
+        href = dict(attrs).get("href") if tag == "a" else None
+        if href and href not in self.links:
+            self.links.append(href)
+
+        if tag in ("script", "style"):
+            self.skip += 1
+        elif tag in self.BLOCKS:
+            self.text.append("\n")
+
+    def handle_endtag(self, tag):
+        if tag in ("script", "style"):
+            self.skip = max(self.skip - 1, 0)
+        elif tag in self.BLOCKS:
+            self.text.append("\n")
+
+    def handle_data(self, data):
+        if not self.skip:
+            self.text.append(data.replace("\n", " "))
+
+
+def stripTags(html):
+
+    # Plain text of an HTML fragment, one paragraph per line
+
+    parser = TextParser()
+    parser.feed(html)
+
+    lines = [" ".join(line.split()) for line in "".join(parser.text).split("\n")]
+
+    return "\n".join(line for line in lines if line)
+
+
+def findLinks(html, base):
+
+    # Links in the content, for opening by number from [I]
+
+    parser = TextParser()
+    parser.feed(html)
+
+    links = [urllib.parse.urljoin(base, href) for href in parser.links]
+
+    return [link for link in links if link.startswith(("http://", "https://")) and link != base]
+
+
+def findImage(feed):
+
+    # Lead image from media:thumbnail, media:content or an image enclosure
+
+    for media in feed.get("media_thumbnail", []) + feed.get("media_content", []):
+        if media.get("url") and media.get("medium", "image") == "image" and media.get("type", "image/").startswith("image/"):
+            return media["url"]
+
+    for link in feed.get("links", []):
+        if link.get("rel") == "enclosure" and link.get("type", "").startswith("image/"):
+            return link.get("href")
+
+    return None
+
+
+def findEnclosure(feed):
+
+    # First audio or video enclosure, for podcasts
+
+    for link in feed.get("links", []):
+        if link.get("rel") == "enclosure" and link.get("type", "").split("/")[0] in ("audio", "video"):
+            return {"url": link.get("href"), "type": link.get("type"), "length": link.get("length")}
+
+    return None
+
+
+def authHandlers(url, option):
+
+    # Credentials for private feeds: username/password on the feed object, in the url,
+    # or under the feed's host in "auth" in config.json. Returns the url without them
+
+    u = urllib.parse.urlparse(url)
+
+    if u.username:
+        creds = {"username": urllib.parse.unquote(u.username), "password": urllib.parse.unquote(u.password or "")}
+        url = u._replace(netloc=u.netloc.rsplit("@", 1)[1]).geturl()
+    elif isinstance(option, dict) and "username" in option:
+        creds = option
+    else:
+        creds = SETTINGS.get("auth", {}).get(u.hostname)
+
+    if not creds:
+        return url, []
+
+    mgr = urllib.request.HTTPPasswordMgrWithDefaultRealm()
+    mgr.add_password(None, url, creds["username"], creds.get("password", ""))
+
+    return url, [urllib.request.HTTPDigestAuthHandler(mgr), urllib.request.HTTPBasicAuthHandler(mgr)]
+
+
+def failed(d):
+
+    # Network errors, timeouts, throttling and server errors are worth retrying; other 4xx are not
+
+    status = d.get("status", 200)
+
+    return not d.entries and (status >= 500 or status in (408, 429) or (d.get("bozo") and "status" not in d))
+
+
+def errorOf(d):
+
+    # Why a fetch gave nothing, for the errors view: HTTP status, network or parse error
+
+    if d.get("status", 200) >= 400:
+        return "HTTP %d" % d["status"]
+
+    if d.entries:
+        return None
+
+    if d.get("bozo"):
+        return str(d.get("bozo_exception")) or d["bozo_exception"].__class__.__name__
+
+    if not d.get("version"):
+        return "Not a feed"
+
+    return None
+
+
+def fetchFeed(url, handlers):
+
+    retry = dict({"attempts": 3, "backoff": 1, "jitter": 0.5}, **SETTINGS.get("retry", {}))
+
+    for attempt in range(max(retry["attempts"], 1)):
+        if attempt:
+            time.sleep(retry["backoff"] * 2 ** (attempt - 1) + random.uniform(0, retry["jitter"]))
+
+        if isHN(url):
+            d = parseHN(url)
+        else:
+            url = youtubeFeedURL(url) or url
+            wait(url)
+            d = feedparser.parse(url, handlers=handlers)
+
+        if not failed(d):
+            break
+
+    return d
+
+
+def do(target_category=None, log=False, progress=None):
+
+    # progress(n, total, source) is called before each feed of the category is fetched
+
+    def getFeedFromRSS(category, urls, show_author=False, include=None, mute=None, log=False):
+
+        rslt, errors = {}, {}
+
+        mute = SETTINGS.get("mute", []) + (mute or [])
+
+        # Entries from the last fetch, kept for sources that fail this time
+
+        previous = (readJSON(os.path.join(p["path_cache"], f"rss_{category}.json")) or {}).get("entries")
+
+        for n, (source, option) in enumerate(urls.items()):
+            if progress:
+                progress(n + 1, len(urls), source)
+
+            url, handlers = authHandlers(option["url"] if isinstance(option, dict) else option, option)
 
-        for source, url in urls.items():
             try:
                 if log:
                     sys.stdout.write(f"- {url}")
 
-                d = feedparser.parse(url)
+                d = fetchFeed(url, handlers)
+
+                # A site URL instead of a feed URL: look for <link rel="alternate"> in the page
+
+                if not failed(d) and not d.entries and not d.get("version"):
+                    feedURL = findFeedURL(url)
+                    if feedURL:
+                        d = fetchFeed(feedURL, handlers)
 
                 if log:
-                    sys.stdout.write(" - Done\n")
+                    sys.stdout.write(" - Failed\n" if failed(d) else " - Done\n")
 
             except:
                 sys.exit(" - Failed\n" if log else 0)
 
+            if errorOf(d):
+                errors[source] = {"url": url, "error": errorOf(d), "at": int(time.time())}
+
+            if failed(d):
+                for entry in previous or []:
+                    if entry.get("source", entry["sourceName"]) == source:
+                        rslt[entry["id"]] = entry
+                continue
+
+            stats = redditStats(url) if isReddit(url) else None
+
             for feed in d.entries:
 
+                if include and not matchesAny(feed.get("title", ""), include):
+                    continue
+
+                if mute and matchesAny(feed.get("title", ""), mute):
+                    continue
+
+                # RSS 1.0 / RDF items carry dc:date, which feedparser exposes as updated_parsed
+
+                parsed = feed.get("published_parsed") or feed.get("updated_parsed")
+
                 try:
-                    at = datetime.datetime(*feed.published_parsed[:6]).replace(tzinfo=datetime.timezone.utc).astimezone(TIMEZONE)
+                    ts = calendar.timegm(parsed)
                 except:
                     continue
 
-                pubDate = at.strftime("%H:%M" if at.date() == datetime.date.today() else "%b %d, %H:%M")
+                # Entries are identified by the feed's GUID, or by a hash of the link when there is none
 
-                ts = int(time.mktime(feed.published_parsed))
+                guid = feed.get("id") or hashlib.sha1(feed.get("link", "").encode("utf-8")).hexdigest()
 
                 entries = {
-                    "id": ts,
+                    "id": guid,
+                    "source": source,
                     "sourceName": source if not show_author else feed.author,
-                    "pubDate": pubDate,
+                    "pubDate": formatTime(ts),
                     "timestamp": ts,
                     "url": feed.link,
                     "title": feed.title,
+                    "author": feed.get("author", ""),
+                    "tags": [tag.term for tag in feed.get("tags", []) if tag.get("term")],
+                    "description": stripTags(feed.content[0].value if feed.get("content") else feed.get("summary", "")),
+                    "contentLinks": findLinks(feed.content[0].value if feed.get("content") else feed.get("summary", ""), feed.link),
+                    "image": findImage(feed),
+                    "enclosure": findEnclosure(feed),
+                }
+
+                # Fields as the feed wrote them, shown with [!] to find out why an entry looks odd
+
+                entries["raw"] = {
+                    "feed": url,
+                    "guid": feed.get("id", ""),
+                    "published": feed.get("published") or feed.get("updated", ""),
+                    "author": feed.get("author", ""),
+                    "categories": [tag.get("term", "") for tag in feed.get("tags", [])],
+                    "links": [" ".join(d for d in (link.get("rel", ""), link.get("type", ""), link.get("href", "")) if d) for link in feed.get("links", [])],
+                    "enclosures": [" ".join(str(d) for d in (enclosure.get("type", ""), enclosure.get("length", ""), enclosure.get("href", "")) if d) for enclosure in feed.get("enclosures", [])],
                 }
 
-                rslt[entries["id"]] = entries
+                # <comments> of RSS items, like Hacker News's, is opened with [C]
+
+                if feed.get("comments"):
+                    entries["commentsUrl"] = feed.comments
+
+                if isHN(url):
+                    entries["points"], entries["comments"] = feed.points, feed.num_comments
+
+                if stats is not None:
+                    entries.update(redditFields(feed, stats))
+
+                entries = transform(entries, category)
+
+                if entries:
+                    rslt[entries["id"]] = entries
+
+        rslt = prune(sorted(rslt.values(), key=lambda entry: entry["timestamp"], reverse=True))
+
+        # Nothing counts as new on the first fetch of a category. Pruned first, since the previous cache was
+
+        if previous is not None:
+            known = set(entry["id"] for entry in previous)
+            runCommands(category, [entry for entry in rslt if entry["id"] not in known])
+
+        try:
+            addToIndex(category, rslt)
+        except Exception:  # the index is optional, feeds are read without it
+            pass
+
+        rslt = {"entries": rslt, "errors": errors, "created_at": int(time.time())}
 
-        rslt = [val for key, val in sorted(rslt.items(), reverse=True)]
+        writeJSON(os.path.join(p["path_cache"], f"rss_{category}.json"), rslt)
 
-        rslt = {"entries": rslt, "created_at": int(time.time())}
+        # Pages are downloaded once the cache is written, on a thread of their own, so neither the reader
+        # nor rr serve waits for them; from the command line, before it exits
 
-        with open(os.path.join(p["path_data"], f"rss_{category}.json"), "w", encoding="utf-8") as f:
-            f.write(json.dumps(rslt, ensure_ascii=False))
+        if SETTINGS.get("prefetch") and log:
+            prefetch(rslt["entries"])
+        elif SETTINGS.get("prefetch"):
+            threading.Thread(target=prefetch, args=(rslt["entries"],), daemon=True).start()
 
         return rslt
 
-    if not os.path.isfile(FEEDS_FILE_NAME):
-        shutil.copyfile(os.path.join(os.path.dirname(os.path.abspath(__file__)), "feeds.json"), FEEDS_FILE_NAME)
+    if not os.path.isfile(p["file_feeds"]):
+        shutil.copyfile(os.path.join(os.path.dirname(os.path.abspath(__file__)), "feeds.json"), p["file_feeds"])
 
-    with open(FEEDS_FILE_NAME, "r") as fp:
-        RSS = json.load(fp)
+    RSS = loadJSON(p["file_feeds"])
 
     if target_category:
-        return getFeedFromRSS(target_category, RSS[target_category]["feeds"], show_author=RSS[target_category].get("show_author", False), log=log)
+        return getFeedFromRSS(target_category, RSS[target_category]["feeds"], show_author=RSS[target_category].get("show_author", False), include=RSS[target_category].get("include"), mute=RSS[target_category].get("mute"), log=log)
 
     for category, d in RSS.items():
-        getFeedFromRSS(category, d["feeds"], show_author=d.get("show_author", False), log=log)
+        getFeedFromRSS(category, d["feeds"], show_author=d.get("show_author", False), include=d.get("include"), mute=d.get("mute"), log=log)
 
 
 if __name__ == "__main__":
@@ -0,0 +1,46 @@
+import json
+import os
+import sys
+import time
+
+from .common import p, readJSON
+from .config import formatTime
+from .get_rss import do as getFeedFromRSS
+
+
+def getFeed(category, ttl):
+
+    d = readJSON(os.path.join(p["path_cache"], f"rss_{category}.json"))
+
+    if d and int(d.get("created_at", 0)) + ttl >= int(time.time()):
+        return d
+
+    return getFeedFromRSS(category)
+
+
+def do(categories, fmt="plain", ttl=lambda category: 120):
+
+    # ttl gives how old the cache of a category may be, in seconds
+
+    rows = []
+
+    for category in categories:
+        for entry in getFeed(category, ttl(category))["entries"]:
+            rows.append(dict(entry, category=category))
+
+    try:
+        if fmt == "json":
+            sys.stdout.write(json.dumps(rows, ensure_ascii=False, indent=2) + "\n")
+            return
+
+        for entry in rows:
+            if fmt == "tsv":
+                fields = [entry["category"], entry["timestamp"], formatTime(entry["timestamp"]), entry["sourceName"], entry["title"], entry["url"]]
+                sys.stdout.write("\t".join(" ".join(str(d).split()) for d in fields) + "\n")
+            else:
+                sys.stdout.write(f"{formatTime(entry['timestamp']):>13}  {entry['sourceName']}: {entry['title']}  {entry['url']}\n")
+
+        sys.stdout.flush()
+
+    except BrokenPipeError:  # the reading end (head, fzf) went away
+        os.dup2(os.open(os.devnull, os.O_WRONLY), sys.stdout.fileno())
@@ -0,0 +1,56 @@
+import json
+import time
+
+import feedparser
+
+from .net import fetch
+
+# Hacker News lists read from the Algolia API, added to feeds.json as hn:front_page, hn:show_hn, hn:ask_hn or hn:newest
+
+API = "https://hn.algolia.com/api/v1/"
+ITEM = "https://news.ycombinator.com/item?id=%s"
+
+
+def isHN(url):
+
+    return url.startswith("hn:")
+
+
+def parse(url):
+
+    # Returns a feedparser-like result; entries link to the story, with the discussion in "comments"
+
+    name = url[3:] or "front_page"
+
+    if name == "newest":
+        query = "search_by_date?tags=story&hitsPerPage=50"
+    else:
+        query = "search?tags=%s&hitsPerPage=50" % name
+
+    try:
+        body, headers = fetch(API + query)
+        hits = json.loads(body.decode("utf-8")).get("hits", [])
+    except Exception as e:
+        return feedparser.FeedParserDict({"entries": [], "bozo": True, "bozo_exception": e})
+
+    entries = []
+
+    for hit in hits:
+        discussion = ITEM % hit["objectID"]
+        entries.append(
+            feedparser.FeedParserDict(
+                {
+                    "id": discussion,
+                    "title": hit.get("title") or "",
+                    "link": hit.get("url") or discussion,
+                    "author": hit.get("author") or "",
+                    "published_parsed": time.gmtime(hit.get("created_at_i", 0)),
+                    "summary": hit.get("story_text") or "",
+                    "comments": discussion,
+                    "points": hit.get("points") or 0,
+                    "num_comments": hit.get("num_comments") or 0,
+                }
+            )
+        )
+
+    return feedparser.FeedParserDict({"entries": entries, "version": "hn"})
@@ -0,0 +1,86 @@
+import importlib.util
+import json
+import os
+import subprocess
+import threading
+
+from .common import p
+from .config import SETTINGS
+
+# hooks.py in the config dir, a Python script that can define
+#   entry(entry, category) -> a changed entry, None or False to drop it; anything else keeps it as it was
+#   KEYS = {"z": function(entry) -> message to show or None}, for keys that are not built in
+
+MODULE = {}
+
+
+def load():
+
+    if "module" not in MODULE:
+        path = os.path.join(p["path_config"], "hooks.py")
+        MODULE["module"] = None
+
+        if os.path.isfile(path):
+            spec = importlib.util.spec_from_file_location("rreader_hooks", path)
+            module = importlib.util.module_from_spec(spec)
+            spec.loader.exec_module(module)
+            MODULE["module"] = module
+
+    return MODULE["module"]
+
+
+def transform(entry, category):
+
+    fn = getattr(load(), "entry", None)
+
+    if not fn:
+        return entry
+
+    try:
+        rslt = fn(entry, category)
+    except Exception:  # a broken hook leaves the entry as it is
+        return entry
+
+    if rslt is None or rslt is False:
+        return None
+
+    return rslt if isinstance(rslt, dict) else entry
+
+
+def keys():
+
+    return getattr(load(), "KEYS", {})
+
+
+def runCommands(category, entries):
+
+    # "onNewEntry" runs once per new entry with it as JSON on stdin, "onNewEntries" once with all of them
+
+    if not entries:
+        return
+
+    inputs = []
+
+    if SETTINGS.get("onNewEntry"):
+        inputs += [(SETTINGS["onNewEntry"], dict(entry, category=category)) for entry in entries]
+
+    if SETTINGS.get("onNewEntries"):
+        inputs.append((SETTINGS["onNewEntries"], [dict(entry, category=category) for entry in entries]))
+
+    for command, d in inputs:
+        try:
+            proc = subprocess.Popen(command, shell=True, stdin=subprocess.PIPE, stdout=subprocess.DEVNULL, stderr=subprocess.DEVNULL, start_new_session=True)
+        except OSError:
+            continue
+
+        threading.Thread(target=feed, args=(proc, json.dumps(d, ensure_ascii=False).encode("utf-8"))).start()
+
+
+def feed(proc, data):
+
+    # On a thread of its own, so a command that does not read its input never holds up the fetch
+
+    try:
+        proc.communicate(data, timeout=60)
+    except (OSError, subprocess.TimeoutExpired):
+        pass
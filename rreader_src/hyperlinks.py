@@ -0,0 +1,42 @@
+import os
+import sys
+import urllib.parse
+
+from .config import SETTINGS
+
+
+def supported():
+
+    # "hyperlinks": false in config.json turns them off; terminals without OSC 8 ignore them anyway
+
+    return SETTINGS.get("hyperlinks", True) and sys.stdout.isatty() and os.environ.get("TERM") not in ("dumb", "linux")
+
+
+def color(n, base):
+
+    # SGR for a palette index, base 30 for the foreground and 40 for the background
+
+    if n < 8:
+        return "%d" % (base + n)
+
+    if n < 16:
+        return "%d" % (base + 60 + n - 8)
+
+    return "%d;5;%d" % (base + 8, n)
+
+
+def draw(links):
+
+    # asciimatics cannot pass escape sequences through, so text it already drew is written again
+    # inside OSC 8 links: links is [(x, y, text, url, fg, bg)]. The cursor and attributes are saved
+    # and restored around it, so the next refresh carries on where asciimatics left off
+
+    out = []
+
+    for x, y, text, url, fg, bg in links:
+        url = urllib.parse.quote(url, safe="".join(chr(i) for i in range(33, 127)))
+        out.append("\0337\033[%d;%dH\033[0;%s;%sm\033]8;;%s\033\\%s\033]8;;\033\\\0338" % (y + 1, x + 1, color(fg, 30), color(bg, 40), url, text))
+
+    if out:
+        sys.stdout.write("".join(out))
+        sys.stdout.flush()
@@ -0,0 +1,51 @@
+import io
+import urllib.parse
+from html.parser import HTMLParser
+
+from asciimatics.renderers import ColourImageFile
+
+from .net import fetch, text
+
+
+class MetaImageParser(HTMLParser):
+    def __init__(self):
+        super().__init__()
+        self.image = None
+
+    def handle_starttag(self, tag, attrs):
+        attrs = dict(attrs)
+
+        if tag == "meta" and not self.image and (attrs.get("property") or attrs.get("name")) in ("og:image", "twitter:image"):
+            self.image = attrs.get("content")
+
+
+def leadImageURL(entry):
+
+    if entry.get("image"):
+        return entry["image"]
+
+    try:
+        body, headers = fetch(entry["url"])
+    except:
+        return None
+
+    parser = MetaImageParser()
+    parser.feed(text(body, headers))
+
+    return urllib.parse.urljoin(entry["url"], parser.image) if parser.image else None
+
+
+def render(screen, entry, height):
+
+    # Text and colour map of the entry's lead image drawn in terminal cells, or None
+
+    url = leadImageURL(entry)
+
+    if not url:
+        return None
+
+    try:
+        body, headers = fetch(url)
+        return ColourImageFile(screen, io.BytesIO(body), height=height).rendered_text
+    except:
+        return None
@@ -0,0 +1,60 @@
+import email.message
+import email.utils
+import shlex
+import smtplib
+import subprocess
+
+from .articles import read as readArticle
+from .config import SETTINGS
+
+# "mail" in config.json, e.g.
+#   {"to": "mom@example.com", "from": "me@example.com", "sendmail": "sendmail -t"}
+#   {"to": "mom@example.com", "from": "me@example.com", "smtp": {"host": "smtp.example.com", "port": 587, "username": "...", "password": "..."}}
+
+
+def message(entry, to):
+
+    d = SETTINGS["mail"]
+
+    msg = email.message.EmailMessage()
+    msg["Subject"] = entry.get("title", "")
+    msg["From"] = d["from"]
+    msg["To"] = to
+    msg["Date"] = email.utils.formatdate(localtime=True)
+
+    text = readArticle(entry) or entry.get("description", "")
+
+    msg.set_content("%s\n%s\n\n%s\n%s\n" % (entry.get("title", ""), entry["url"], entry.get("sourceName", ""), "\n\n".join(text.split("\n"))))
+
+    return msg
+
+
+def send(entry, to=None):
+
+    # Returns an error message, or None when the mail was sent
+
+    d = SETTINGS.get("mail")
+
+    if not d:
+        return "No mail set up"
+
+    try:
+        msg = message(entry, to or d["to"])
+
+        if d.get("smtp"):
+            smtp = d["smtp"]
+            with smtplib.SMTP_SSL(smtp["host"], smtp.get("port", 465), timeout=30) if smtp.get("ssl") else smtplib.SMTP(smtp["host"], smtp.get("port", 587), timeout=30) as server:
+                if not smtp.get("ssl") and smtp.get("starttls", True):
+                    server.starttls()
+                if smtp.get("username"):
+                    server.login(smtp["username"], smtp.get("password", ""))
+                server.send_message(msg)
+        else:
+            subprocess.run(shlex.split(d.get("sendmail", "sendmail -t")), input=msg.as_bytes(), check=True, timeout=30)
+
+    except KeyError as e:
+        return "mail needs %s" % e
+    except Exception as e:
+        return "Mail failed: %s" % e
+
+    return None
@@ -0,0 +1,150 @@
+import base64
+import gzip
+import os
+import re
+import socket
+import sys
+import threading
+import time
+import urllib.parse
+import urllib.request
+import zlib
+
+from .config import SETTINGS
+
+try:
+    import brotli
+except ImportError:
+    brotli = None
+
+USER_AGENT = "rreader (+https://github.com/rainygirl/rreader)"
+
+# feedparser asks for compressed feeds by itself; this is for the other requests. Brotli needs the brotli package
+
+ACCEPT_ENCODING = "gzip, deflate" + (", br" if brotli else "")
+
+# When the next request to each host may start
+
+HOSTS, HOSTLOCK = {}, threading.Lock()
+
+
+def wait(url):
+
+    # Spaces out requests to the same host by "hostDelay" seconds from config.json
+
+    delay = SETTINGS.get("hostDelay", 1)
+    host = urllib.parse.urlparse(url).hostname
+
+    with HOSTLOCK:
+        at = max(time.time(), HOSTS.get(host, 0))
+        HOSTS[host] = at + delay
+
+    if at > time.time():
+        time.sleep(at - time.time())
+
+
+def decompress(body, encoding):
+
+    encoding = (encoding or "").lower()
+
+    if encoding in ("gzip", "x-gzip"):
+        return gzip.decompress(body)
+
+    if encoding == "deflate":
+        try:
+            return zlib.decompress(body)
+        except zlib.error:
+            return zlib.decompress(body, -zlib.MAX_WBITS)
+
+    if encoding == "br" and brotli:
+        return brotli.decompress(body)
+
+    return body
+
+
+def text(body, headers):
+
+    # The charset from the HTTP header, then from <meta charset> or the XML declaration,
+    # then UTF-8 and Windows-1252
+
+    charsets = [headers.get_content_charset()]
+
+    m = re.search(rb"<meta[^>]+charset=[\"']?([\w-]+)|<\?xml[^>]+encoding=[\"']([\w-]+)", body[:4096], re.IGNORECASE)
+
+    if m:
+        charsets.append((m.group(1) or m.group(2)).decode("ascii"))
+
+    for charset in charsets + ["utf-8", "cp1252"]:
+        try:
+            return body.decode(charset)
+        except (TypeError, LookupError, UnicodeDecodeError):
+            continue
+
+    return body.decode("utf-8", "replace")
+
+
+def fetch(url, timeout=10, data=None, headers=None, auth=None):
+
+    # GET, or POST when data is given; auth is a (username, password) pair for HTTP basic auth
+
+    headers = dict({"User-Agent": USER_AGENT, "Accept-Encoding": ACCEPT_ENCODING}, **(headers or {}))
+
+    if auth:
+        headers["Authorization"] = "Basic " + base64.b64encode(("%s:%s" % auth).encode("utf-8")).decode("ascii")
+
+    req = urllib.request.Request(url, data=data, headers=headers)
+
+    wait(url)
+
+    with urllib.request.urlopen(req, timeout=timeout) as r:
+        return decompress(r.read(), r.headers.get("Content-Encoding")), r.headers
+
+
+def rewrite(url):
+
+    # "rewrite" in config.json: a host, with its subdomains, is swapped for another host,
+    # and a /.../ regular expression is replaced in the whole url, backreferences allowed
+
+    for pattern, replacement in SETTINGS.get("rewrite", {}).items():
+        if len(pattern) > 2 and pattern.startswith("/") and pattern.endswith("/"):
+            try:
+                url = re.sub(pattern[1:-1], replacement, url)
+            except re.error:
+                continue
+        else:
+            # hostname comes lowercased, whatever the case in the url or in config.json
+
+            u = urllib.parse.urlparse(url)
+            host, pattern = u.hostname or "", pattern.lower()
+            if host == pattern or host.endswith("." + pattern):
+                i = u.netloc.lower().rfind(host)
+                url = u._replace(netloc=u.netloc[:i] + replacement + u.netloc[i + len(host) :]).geturl()
+
+    return url
+
+
+def setProxy(url=None):
+
+    # "proxy" from config.json, else ALL_PROXY; HTTP_PROXY and HTTPS_PROXY are honored by urllib as they are
+
+    override = bool(url)
+    url = url or os.environ.get("ALL_PROXY") or os.environ.get("all_proxy")
+
+    if not url:
+        return
+
+    u = urllib.parse.urlparse(url)
+
+    if u.scheme.startswith("socks"):
+        try:
+            import socks
+        except ImportError:
+            sys.exit("SOCKS proxies need PySocks: pip install pysocks")
+
+        socks.set_default_proxy(socks.SOCKS4 if u.scheme.startswith("socks4") else socks.SOCKS5, u.hostname, u.port or 1080, rdns=u.scheme in ("socks5h", "socks4a"), username=u.username, password=u.password)
+        socket.socket = socks.socksocket
+        return
+
+    for key in ("http_proxy", "https_proxy"):
+        if override or not (os.environ.get(key) or os.environ.get(key.upper())):
+            os.environ[key] = url
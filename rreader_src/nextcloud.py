@@ -0,0 +1,106 @@
+import json
+import os
+import sys
+
+from .cache import entries as cachedEntries
+from .common import p, readJSON, writeJSON
+from .config import SETTINGS
+from .net import fetch
+from .opml import categoryKey, mergeFeeds
+from .state import READ, mergeRead, saveState
+
+# Feeds outside of any folder go to this category
+
+DEFAULT_CATEGORY = ("nextcloud", "Nextcloud")
+
+
+def get(path, d):
+
+    body, headers = fetch(d["url"].rstrip("/") + "/index.php/apps/news/api/v1-3/" + path, auth=(d["username"], d["password"]))
+
+    return json.loads(body)
+
+
+def markReadThere(ids, d):
+
+    if ids:
+        fetch(d["url"].rstrip("/") + "/index.php/apps/news/api/v1-3/items/read/multiple", data=json.dumps({"itemIds": ids}).encode("utf-8"), headers={"Content-Type": "application/json"}, auth=(d["username"], d["password"]))
+
+
+def syncRead(d, log=True):
+
+    # Read state both ways, matched by GUID or link. Item ids are kept in nextcloud.json in the cache dir,
+    # so entries opened later are marked read there at once, with markRead
+
+    local = {}
+    for entry in cachedEntries():
+        for key in (entry["id"], entry.get("url")):
+            local.setdefault(key, []).append(entry["id"])
+
+    known, pulled, pushed = {}, [], []
+
+    for item in get("items?type=3&id=0&getRead=true&batchSize=1000", d)["items"]:
+        ids = local.get(item.get("guid")) or local.get(item.get("url")) or []
+
+        for id in ids:
+            known[id] = item["id"]
+
+        if not item.get("unread"):
+            pulled += ids
+        elif any(id in READ for id in ids):
+            pushed.append(item["id"])
+
+    markReadThere(pushed, d)
+
+    pulled = list(dict.fromkeys(id for id in pulled if id not in READ))
+    mergeRead(pulled)
+    saveState()
+
+    writeJSON(os.path.join(p["path_cache"], "nextcloud.json"), known)
+
+    if log:
+        sys.stdout.write(f"Marked {len(pulled)} entries read here and {len(pushed)} in Nextcloud News\n")
+
+
+def markRead(id):
+
+    # An entry opened in rReader, marked read in Nextcloud News when rr sync has seen it there
+
+    d = SETTINGS.get("nextcloud")
+    known = readJSON(os.path.join(p["path_cache"], "nextcloud.json"), {})
+
+    if d and id in known:
+        markReadThere([known[id]], d)
+
+
+def do(log=True):
+
+    # "nextcloud" in config.json: {"url": "https://cloud.example.com", "username": "...", "password": "..."}
+
+    d = SETTINGS.get("nextcloud")
+
+    if not d or not all(key in d for key in ("url", "username", "password")):
+        sys.exit("Set nextcloud url, username and password in config.json")
+
+    try:
+        folders = {folder["id"]: folder["name"] for folder in get("folders", d)["folders"]}
+        feeds = get("feeds", d)["feeds"]
+    except Exception as e:
+        sys.exit(f"Nextcloud News: {e}")
+
+    imported = {}
+
+    for feed in feeds:
+        name = folders.get(feed.get("folderId"))
+        key, title = (categoryKey(name), name) if name else DEFAULT_CATEGORY
+        category = imported.setdefault(key, {"title": title, "feeds": {}})
+        category["feeds"][feed.get("title") or feed["url"]] = feed["url"]
+
+    mergeFeeds(imported, log=log)
+
+    try:
+        syncRead(d, log=log)
+    except Exception as e:
+        sys.exit(f"Nextcloud News: {e}")
+
+    return imported
@@ -0,0 +1,59 @@
+import datetime
+import os
+import re
+
+from .articles import read as readArticle
+from .config import SETTINGS, timezone
+
+# [Shift]+[M]: the entry as a Markdown file in "notesDir", for Obsidian and the like
+
+
+def fileName(entry, category):
+
+    # "noteName" with {date}, {time}, {title}, {source} and {category}; characters that cannot be in file names are dropped
+
+    at = datetime.datetime.fromtimestamp(entry.get("timestamp", 0), timezone())
+    fields = {"date": at.strftime("%Y-%m-%d"), "time": at.strftime("%H%M"), "title": entry.get("title", ""), "source": entry.get("sourceName", ""), "category": category}
+    fields = {key: re.sub(r'[\\/:*?"<>|#^\[\]]+', " ", str(value)).strip() for key, value in fields.items()}
+
+    # A template with an unknown {placeholder} or a stray brace falls back to the default one
+
+    try:
+        name = SETTINGS.get("noteName", "{date} {title}").format(**fields)
+    except (KeyError, IndexError, ValueError):
+        name = "{date} {title}".format(**fields)
+
+    return " ".join(name.split())[:120] + ".md"
+
+
+def save(entry, category):
+
+    # Returns the path of the note
+
+    at = datetime.datetime.fromtimestamp(entry.get("timestamp", 0), timezone())
+    quote = lambda s: '"%s"' % s.replace("\\", "\\\\").replace('"', '\\"')
+
+    lines = [
+        "---",
+        "title: " + quote(entry.get("title", "")),
+        "source: " + quote(entry.get("sourceName", "")),
+        "url: " + entry.get("url", ""),
+        "date: " + at.isoformat(),
+        "tags: [%s]" % ", ".join(quote(tag) for tag in entry.get("tags", [])),
+        "---",
+        "",
+        "# " + entry.get("title", ""),
+        "",
+        "[%s](%s)" % (entry.get("sourceName", ""), entry.get("url", "")),
+        "",
+        "\n\n".join((readArticle(entry) or entry.get("description", "")).split("\n")),
+    ]
+
+    path = os.path.expanduser(SETTINGS.get("notesDir", "~/Notes"))
+    os.makedirs(path, exist_ok=True)
+    path = os.path.join(path, fileName(entry, category))
+
+    with open(path, "w", encoding="utf-8") as fp:
+        fp.write("\n".join(lines) + "\n")
+
+    return path
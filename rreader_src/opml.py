@@ -0,0 +1,95 @@
+import os
+import re
+import shutil
+import sys
+import xml.etree.ElementTree as ET
+
+from .common import loadJSON, p, writeJSON
+
+# OPML folders become categories; feeds outside of any folder go to this one
+
+DEFAULT_CATEGORY = ("imported", "Imported")
+
+
+def categoryKey(title):
+
+    key = re.sub(r"[^0-9a-z]+", "_", title.lower()).strip("_")
+
+    return key or DEFAULT_CATEGORY[0]
+
+
+def uniqueName(feeds, name):
+
+    # A second feed with a name already taken becomes "Name (2)", and so on, instead of replacing the first
+
+    rslt, n = name, 1
+
+    while rslt in feeds:
+        n += 1
+        rslt = "%s (%d)" % (name, n)
+
+    return rslt
+
+
+def parse(filename):
+
+    rslt = {}
+
+    def walk(node, folder):
+        for outline in node.findall("outline"):
+            url = outline.get("xmlUrl")
+            title = outline.get("title") or outline.get("text") or url
+
+            if url:
+                key, name = folder or DEFAULT_CATEGORY
+                category = rslt.setdefault(key, {"title": name, "feeds": {}})
+                if url not in category["feeds"].values():
+                    category["feeds"][uniqueName(category["feeds"], title)] = url
+            else:
+                walk(outline, folder or (categoryKey(title), title))
+
+    body = ET.parse(filename).getroot().find("body")
+
+    if body is not None:
+        walk(body, None)
+
+    return rslt
+
+
+def mergeFeeds(imported, log=True):
+
+    # Adds {category: {"title": ..., "feeds": {...}}} to feeds.json, skipping feeds already there in any category
+
+    if not os.path.isfile(p["file_feeds"]):
+        shutil.copyfile(os.path.join(os.path.dirname(os.path.abspath(__file__)), "feeds.json"), p["file_feeds"])
+
+    RSS = loadJSON(p["file_feeds"])
+
+    count = 0
+
+    urls = set(d["url"] if isinstance(d, dict) else d for category in RSS.values() for d in category["feeds"].values())
+
+    for key, category in imported.items():
+        target = RSS.setdefault(key, {"title": category["title"], "feeds": {}})
+        for source, url in category["feeds"].items():
+            if url not in urls:
+                target["feeds"][uniqueName(target["feeds"], source)] = url
+                urls.add(url)
+                count += 1
+
+    writeJSON(p["file_feeds"], RSS, indent=4)
+
+    if log:
+        sys.stdout.write(f"Imported {count} feeds in {len(imported)} categories into {p['file_feeds']}\n")
+
+
+def do(filename, log=True):
+
+    try:
+        imported = parse(filename)
+    except (OSError, ET.ParseError) as e:
+        sys.exit(f"Cannot read {filename}: {e}")
+
+    mergeFeeds(imported, log=log)
+
+    return imported
@@ -0,0 +1,63 @@
+import os
+import shutil
+import subprocess
+import sys
+
+from .articles import extract, read as readArticle
+from .config import formatTime
+from .headlines import getFeed
+from .net import fetch, rewrite, text
+from .text import wrapText
+
+# rr read: the text of a page, or of the entry numbered as in the reader, wrapped to the terminal
+
+
+def entryOf(target, category, ttl):
+
+    # A number picks the entry of the category, newest first, as numbered in the reader
+
+    if not target.isdigit():
+        return {"url": target}
+
+    entries = getFeed(category, ttl(category))["entries"]
+
+    if not 1 <= int(target) <= len(entries):
+        sys.exit(f"No entry {target} in {category}")
+
+    return entries[int(target) - 1]
+
+
+def do(target, category, pager=False, ttl=lambda category: 120):
+
+    entry = entryOf(target, category, ttl)
+    article = readArticle(entry) if "id" in entry else None
+
+    if not article:
+        try:
+            body, headers = fetch(rewrite(entry["url"]))
+        except Exception as e:
+            sys.exit(f"Cannot fetch {entry['url']}: {e}")
+        article = extract(text(body, headers)) or entry.get("description", "")
+
+    width = min(shutil.get_terminal_size().columns, 100)
+    lines = []
+
+    if entry.get("title"):
+        lines += wrapText(entry["title"], width) + ["%s  %s" % (entry.get("sourceName", ""), formatTime(entry["timestamp"])), ""]
+
+    lines += [entry["url"], ""]
+
+    for paragraph in article.split("\n"):
+        lines += (wrapText(paragraph, width) if paragraph.strip() else []) + [""]
+
+    output = "\n".join(lines) + "\n"
+
+    if pager and sys.stdout.isatty():
+        subprocess.run(os.environ.get("PAGER", "less"), shell=True, input=output.encode("utf-8"))
+        return
+
+    try:
+        sys.stdout.write(output)
+        sys.stdout.flush()
+    except BrokenPipeError:  # the reading end went away
+        os.dup2(os.open(os.devnull, os.O_WRONLY), sys.stdout.fileno())
@@ -0,0 +1,76 @@
+import json
+import urllib.parse
+
+from .config import SETTINGS
+from .net import fetch
+
+# "readLater" in config.json, e.g.
+#   {"service": "wallabag", "url": "https://app.wallabag.it", "client_id": "...", "client_secret": "...", "username": "...", "password": "..."}
+#   {"service": "pocket", "consumer_key": "...", "access_token": "..."}
+#   {"service": "instapaper", "username": "...", "password": "..."}
+
+
+def wallabag(d, url, title):
+
+    base = d["url"].rstrip("/")
+
+    body, headers = fetch(
+        base + "/oauth/v2/token",
+        data=urllib.parse.urlencode(
+            {
+                "grant_type": "password",
+                "client_id": d["client_id"],
+                "client_secret": d["client_secret"],
+                "username": d["username"],
+                "password": d["password"],
+            }
+        ).encode("utf-8"),
+    )
+
+    token = json.loads(body)["access_token"]
+
+    fetch(
+        base + "/api/entries.json",
+        data=urllib.parse.urlencode({"url": url, "title": title}).encode("utf-8"),
+        headers={"Authorization": "Bearer " + token},
+    )
+
+
+def pocket(d, url, title):
+
+    fetch(
+        "https://getpocket.com/v3/add",
+        data=json.dumps({"url": url, "title": title, "consumer_key": d["consumer_key"], "access_token": d["access_token"]}).encode("utf-8"),
+        headers={"Content-Type": "application/json; charset=UTF-8", "X-Accept": "application/json"},
+    )
+
+
+def instapaper(d, url, title):
+
+    fetch(
+        "https://www.instapaper.com/api/add",
+        data=urllib.parse.urlencode({"url": url, "title": title}).encode("utf-8"),
+        auth=(d["username"], d.get("password", "")),
+    )
+
+
+SERVICES = {"wallabag": wallabag, "pocket": pocket, "instapaper": instapaper}
+
+
+def save(url, title=""):
+
+    # Returns an error message, or None when the link was saved
+
+    d = SETTINGS.get("readLater")
+
+    if not d or d.get("service") not in SERVICES:
+        return "No read-it-later service set up"
+
+    try:
+        SERVICES[d["service"]](d, url, title)
+    except KeyError as e:
+        return "readLater needs %s" % e
+    except Exception as e:
+        return "Save failed: %s" % e
+
+    return None
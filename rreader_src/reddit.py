@@ -0,0 +1,46 @@
+import json
+import re
+import urllib.parse
+
+from .net import fetch
+
+
+def isReddit(url):
+
+    return re.match(r"^https?://([\w-]+\.)?reddit\.com/", url) is not None
+
+
+def stats(url):
+
+    # Score and comment counts from the JSON listing matching a .rss feed, keyed by post name (t3_...)
+
+    u = urllib.parse.urlparse(url)
+
+    try:
+        body, headers = fetch(u._replace(path=re.sub(r"\.rss$", "", u.path.rstrip("/")) + ".json").geturl())
+        listing = json.loads(body.decode("utf-8"))
+    except:
+        return {}
+
+    return {d["data"]["name"]: d["data"] for d in listing.get("data", {}).get("children", []) if "name" in d.get("data", {})}
+
+
+def fields(feed, stats):
+
+    # The posted link becomes the entry's url and the thread its comments url
+
+    rslt = {"commentsUrl": feed.get("link")}
+
+    html = feed.content[0].value if feed.get("content") else feed.get("summary", "")
+    link = re.search(r'<a href="([^"]+)">\[link\]</a>', html)
+
+    if link:
+        rslt["url"] = link.group(1).replace("&amp;", "&")
+
+    d = stats.get(feed.get("id"))
+
+    if d:
+        rslt["points"] = d.get("score", 0)
+        rslt["comments"] = d.get("num_comments", 0)
+
+    return rslt
@@ -1,8 +1,11 @@
 # -*- coding:utf-8 -*-
 
-import json
+import argparse
+import datetime
 import os
-import re
+import queue
+import shlex
+import subprocess
 import sys
 import signal
 import time
@@ -14,16 +17,49 @@ from asciimatics.effects import Print
 from asciimatics.scene import Scene
 from asciimatics.renderers import ColourImageFile, SpeechBubble
 
-from .common import p, FEEDS_FILE_NAME
+from .articles import links as articleLinks, read as readArticle, save as saveArticle
+from .cache import clean as cleanCache, rebuild as rebuildCache
+from .client import getFeed as getRemoteFeed, markRead as markRemoteRead, server as remoteServer
+from .clipboard import copy as copyToClipboard
+from .colors import detect as detectColors, toIndex
+from .common import BROKEN, loadJSON, p, readJSON, setPaths, migrateLegacyFiles, writeJSON
+from .config import loadSettings, formatTime, timezone, SETTINGS
+from .downloads import add as addDownload, progress as downloadProgress, status as downloadStatus, DOWNLOADS
+from .feedbin import do as syncFeedbin, markRead as markFeedbinRead
+from .fuzzy import rank as fuzzyRank
 from .get_rss import do as getFeedFromRSS
+from .export import do as exportEntries, toFile as exportToFile
+from .headlines import do as listHeadlines
+from .hooks import keys as hookKeys, load as loadHooks
+from .hyperlinks import draw as drawHyperlinks, supported as hyperlinksSupported
+from .images import render as renderImage
+from .net import rewrite, setProxy
+from .nextcloud import do as syncNextcloud, markRead as markNextcloudRead
+from .mail import send as sendMail
+from .notes import save as saveNote
+from .opml import do as importOPML
+from .read import do as readArticleText
+from .readlater import save as saveForLater
+from .search import search as searchIndex
+from .serve import do as serveFeeds
+from .share import configured as shareConfigured, post as share
+from .state import READ, STATE, loadState, markRead, mergeRead, recordOpen, saveState
+from .text import isDoubleChar, textLength, truncateText, wrapText
 
 
 KEY = {
     "up": -204,
     "down": -206,
+    "left": -203,
+    "right": -205,
     "shiftUp": 337,
     "shiftDown": 336,
+    "pageUp": -207,
+    "pageDown": -208,
     "enter": 10,
+    "ctrlF": 6,
+    "ctrlT": 20,
+    "ctrlP": 16,
     "space": 32,
     "tab": -301,
     "shiftTab": -302,
@@ -32,6 +68,13 @@ KEY = {
     ":": ord(":"),
     "h": [ord("h"), ord("H")],
     "?": ord("?"),
+    "/": ord("/"),
+    "+": [ord("+"), ord("=")],
+    "-": ord("-"),
+    "[": ord("["),
+    "]": ord("]"),
+    "n": ord("n"),
+    "N": ord("N"),
     "r": [ord("r"), ord("R")],
     "s": [ord("s"), ord("S")],
     "w": [ord("w"), ord("W")],
@@ -39,29 +82,59 @@ KEY = {
     "k": [ord("k"), ord("K")],
     "o": [ord("o"), ord("O")],
     "q": [ord("q"), ord("Q")],
+    "t": [ord("t"), ord("T")],
+    "g": [ord("g"), ord("G")],
+    "v": [ord("v"), ord("V")],
+    "p": [ord("p"), ord("P")],
+    "y": [ord("y"), ord("Y")],
+    "m": ord("m"),
+    "M": ord("M"),
+    "l": [ord("l"), ord("L")],
+    "a": [ord("a"), ord("A")],
+    "b": [ord("b"), ord("B")],
+    "c": [ord("c"), ord("C")],
+    "d": ord("d"),
+    "e": [ord("e"), ord("E")],
+    "f": [ord("f"), ord("F")],
+    "i": [ord("i"), ord("I")],
+    "u": [ord("u"), ord("U")],
+    "x": [ord("x"), ord("X")],
+    "#": ord("#"),
+    "@": ord("@"),
+    "D": ord("D"),
+    "!": ord("!"),
 }
 
 KEYLIST = {
-    "arrow": [KEY["up"], KEY["down"], KEY["shiftUp"], KEY["shiftDown"], KEY["esc"]] + KEY["s"] + KEY["w"] + KEY["j"] + KEY["k"],
+    "arrow": [KEY["up"], KEY["down"], KEY["shiftUp"], KEY["shiftDown"], KEY["pageUp"], KEY["pageDown"], KEY["esc"], KEY["n"], KEY["N"]] + KEY["s"] + KEY["w"] + KEY["j"] + KEY["k"],
     "number": range(48, 58),
 }
 
 CONFIG = {
-    "color": 16,
-    "mode": "list",
-    "rowlimit": -1,
+    "color": 16,  # 16, 256 or truecolor, from --colors or detected
+    "mode": "list",  # list, group (entries under collapsible source headers), columns
+    "preview": False,  # show the selected entry's content next to or below the list
+    "hideRead": False,  # leave out entries that were opened
+    "today": False,  # only entries published today, in the display timezone
+    "rowlimit": -1,  # number of entries that can be selected; the list scrolls past the screen
+    "marquee": True,  # scroll the selected entry's title when it does not fit
+    "expand": False,  # or wrap it over up to 3 rows
+    "accessible": False,  # for screen readers: no marquee or fills, the selected entry spelled out on the last row
     "marqueeFields": ["title", "text"],
+    "minSize": (60, 10),  # columns and rows below which the list is not drawn
     "marqueeSpeed": 20,
     "marqueeSpeedReturn": 400,
     "marqueeDelay": 40,
     "marqueeDelayReturn": 120,
     "refresh": 120,  # RSS pooling interval (seconds)
+    "refreshOverride": {},  # intervals changed with + and - while running, per category
+    "autoRefresh": True,
+    "category": None,  # category tab to start on
+    "theme": "default",
     "categories": (),
+    "feeds": {},  # contents of feeds.json
 }
 
-if "256" in os.environ.get("TERM", ""):
-    CONFIG["color"] = 256
-
 COLOR = {
     "default": 7,
     "number": 7,
@@ -76,26 +149,68 @@ COLOR = {
     "categorybg": 0,
     "categoryfgS": 0,
     "categorybgS": 3,
+    "background": 0,
+    "selectedfg": 0,
+}
+
+# Used instead when the terminal has 256 colors or more
+
+COLOR256 = {
+    "default": 7,
+    "number": 8,
+    "numberselected": 15,
+    "source": 2,
+    "bluesource": 105,
+    "RTheaderS": 6,
+    "time": 8,
+    "selected": 15,
+    "alertfg": 15,
+    "alertbg": 12,
+    "categoryfg": 223,
+    "categorybg": 235,
+    "categoryfgS": 235,
+    "categorybgS": 223,
+}
+
+# Themes are JSON files in <config dir>/themes/ overriding any of the COLOR keys above,
+# with terminal color numbers or "#rrggbb" values
+
+THEMES = {}
+
+# Built in for light terminals, with the 16 basic colors so it looks the same at any depth
+
+LIGHT = {
+    "background": 15,
+    "default": 0,
+    "number": 8,
+    "numberselected": 15,
+    "source": 4,
+    "bluesource": 4,
+    "time": 8,
+    "selected": 4,
+    "selectedfg": 15,
+    "alertfg": 15,
+    "alertbg": 4,
+    "categoryfg": 0,
+    "categorybg": 7,
+    "categoryfgS": 15,
+    "categorybgS": 4,
+}
+
+SORTS = {
+    "newest": "Newest first",
+    "oldest": "Oldest first",
+    "source": "By source",
+    "title": "By title",
 }
 
+# Refresh intervals in seconds stepped through with + and -
+
+INTERVALS = [30, 60, 120, 300, 600, 1800, 3600]
 
-if CONFIG["color"] == 256:
-    COLOR = {
-        "default": 7,
-        "number": 8,
-        "numberselected": 15,
-        "source": 2,
-        "bluesource": 105,
-        "RTheaderS": 6,
-        "time": 8,
-        "selected": 15,
-        "alertfg": 15,
-        "alertbg": 12,
-        "categoryfg": 223,
-        "categorybg": 235,
-        "categoryfgS": 235,
-        "categorybgS": 223,
-    }
+# Time ranges in hours stepped through with [ and ]; 0 shows everything
+
+RANGES = [1, 6, 12, 24, 72, 168, 720, 0]
 
 # FIELDS syntax : (column, field, color key, space fill)
 
@@ -109,87 +224,398 @@ FIELDS = {
 
 data, CURRENT = {}, {}
 
+# Feeds are fetched on worker threads; results are merged by the main loop
+
+FETCHED, FETCHING = queue.Queue(), set()
+
+# Feed being fetched in each category, as (n, total, source)
+
+PROGRESS = {}
+
+SPINNER = "\u280b\u2819\u2839\u2838\u283c\u2834\u2826\u2827\u2807\u280f"
+
+# Alerts posted by worker threads, shown by the main loop
+
+MESSAGES = queue.Queue()
+
+# Selected entry and scroll position of each category, kept while switching tabs
+
+PLACES = {}
+
+# Lead images for the preview pane, rendered on worker threads and keyed by entry id
+
+IMAGES, IMAGED = {}, queue.Queue()
+
 os.environ.setdefault("ESCDELAY", "10")
 
 
-def getFeed(category="news"):
+def fetchInBackground(category):
+    if category in FETCHING:
+        return
+
+    FETCHING.add(category)
+
+    def worker():
+        try:
+            if remoteServer():
+                d = getRemoteFeed(category)
+                writeJSON(p["path_cache"] + "remote_%s.json" % category, d)
+            else:
+                d = getFeedFromRSS(category, progress=lambda *d: PROGRESS.__setitem__(category, d))
+        except:
+            d = None
+        FETCHED.put((category, d))
+
+    threading.Thread(target=worker, daemon=True).start()
+
+
+def loadThemes():
+    path = os.path.join(p["path_config"], "themes")
+
+    if not os.path.isdir(path):
+        return
+
+    for name in sorted(os.listdir(path)):
+        if name.endswith(".json"):
+            theme = loadJSON(os.path.join(path, name))
+
+            if not isinstance(theme, dict):
+                continue
+
+            THEMES[name[:-5]] = {}
+
+            # A color that cannot be read is left out, so the theme falls back to the default one for it
+
+            for key, value in theme.items():
+                try:
+                    toIndex(value)
+                    THEMES[name[:-5]][key] = value
+                except ValueError as e:
+                    BROKEN.append(f"themes/{name}: {key} skipped, {e}")
+
+
+def setTheme(name):
+    COLOR.clear()
+    COLOR.update(THEMES["default"])
+    COLOR.update({key: toIndex(value, CONFIG["color"]) for key, value in THEMES[name].items()})
+    CONFIG["theme"] = name
+
+
+def readCache(category):
+
+    # With "server", the copy last received from it, kept apart in case the server shares this cache dir;
+    # fetchInBackground asks the server for a newer one, so a slow server never holds up the screen
+
+    if remoteServer():
+        d = readJSON(p["path_cache"] + "remote_%s.json" % category)
+        mergeRead((d or {}).get("read", []))
+        return d
+
+    return readJSON(p["path_cache"] + "rss_%s.json" % category)
+
+
+def feedOption(category, source, key, default=None):
+
+    # A feed's own setting in feeds.json, then its category's, then config.json's
+
+    d = CONFIG["feeds"].get(category, {})
+    feed = d.get("feeds", {}).get(source)
+
+    if isinstance(feed, dict) and key in feed:
+        return feed[key]
+
+    return d.get(key, SETTINGS.get(key, default))
+
+
+def openLink(url, category=None, source=None):
+
+    # "open" is a command template like "mpv {url}"; the browser is used when none is set
+
+    url = rewrite(url)
+    command = feedOption(category, source, "open")
+
+    if not command:
+        return webbrowser.open(url, new=2)
+
+    return runCommand(command, url)
+
+
+def runCommand(command, url):
+
+    # Starts the command detached from the terminal, with {url} replaced or the url appended
+
     try:
-        with open(p["path_data"] + "rss_%s.json" % category, "r") as c:
-            d = json.load(c)
-    except:
-        d = getFeedFromRSS(category)
-        if not d:
-            sys.exit("oops")
-    return d
+        args = shlex.split(command)
+    except ValueError:  # an unbalanced quote
+        return False
+
+    if "{url}" in command:
+        args = [d.replace("{url}", url) for d in args]
+    else:
+        args.append(url)
+
+    try:
+        subprocess.Popen(args, stdin=subprocess.DEVNULL, stdout=subprocess.DEVNULL, stderr=subprocess.DEVNULL, start_new_session=True)
+    except OSError:
+        return False
+
+    return True
+
+
+def runInBackground(task, *args):
+
+    # task returns the message to alert when it is done, or None
+
+    def worker():
+        try:
+            message = task(*args)
+        except Exception as e:
+            message = str(e)
+        if message:
+            MESSAGES.put(message)
+
+    threading.Thread(target=worker, daemon=True).start()
+
+
+def loadImageInBackground(screen, entry, height):
+    if entry["id"] in IMAGES:
+        return
+
+    IMAGES[entry["id"]] = None
+
+    def worker():
+        IMAGES[entry["id"]] = renderImage(screen, entry, height)
+        IMAGED.put(entry["id"])
+
+    threading.Thread(target=worker, daemon=True).start()
+
+
+def refreshInterval(category):
+
+    # "refresh" seconds on the category or in config.json, unless changed with + and -
+
+    return CONFIG["refreshOverride"].get(category) or feedOption(category, None, "refresh", CONFIG["refresh"])
+
+
+def cacheTTL(category):
+
+    # How old the cache of a category can be when it is opened or listed; the refresh interval by default
+
+    return feedOption(category, None, "cacheTTL", refreshInterval(category))
+
 
-    return None
+def getFeed(category="news"):
+    d = readCache(category)
+
+    if d is None or int(d.get("created_at", 0)) + cacheTTL(category) < int(time.time()):
+        fetchInBackground(category)
+
+    return d or {"entries": [], "created_at": 0}
 
 
 def layout(screen):
 
     global data, CURRENT
 
-    def reloadData():
+    def updateView():
 
-        global data, CURRENT
+        entries = data[CURRENT["category"]]["entries"]
 
-        while True:
+        # Full-text search results over every category and past entries replace the category's list
 
-            time.sleep(1)
+        if CURRENT.get("results") is not None:
+            entries = CURRENT["results"]
 
-            ccategory = CURRENT.get("category")
+        if CURRENT.get("source"):
+            entries = [entry for entry in entries if entry.get("source", entry.get("sourceName")) == CURRENT["source"]]
 
-            if (
-                ccategory in data
-                and data[ccategory].get("created_at")
-                and int(data[ccategory].get("created_at")) + CONFIG["refresh"] < int(time.time())
-                and not CONFIG.get("loading")
-            ):
+        if CURRENT.get("results") is None and CURRENT.get("query"):
+            query = CURRENT["query"].lower()
+            entries = [entry for entry in entries if query in entry.get("title", "").lower() or query in entry.get("sourceName", "").lower()]
 
-                CONFIG["loading"] = True
+        if CONFIG["hideRead"]:
+            entries = [entry for entry in entries if entry.get("id") not in READ]
 
-                alert(screen, "UPDATING")
+        hours = timeRange()
 
-                d = getFeedFromRSS(CURRENT["category"])
+        if hours:
+            entries = [entry for entry in entries if entry.get("timestamp", 0) >= time.time() - hours * 3600]
 
-                CONFIG["loading"] = False
+        if CONFIG["today"]:
+            start = datetime.datetime.now(timezone()).replace(hour=0, minute=0, second=0, microsecond=0).timestamp()
+            entries = [entry for entry in entries if entry.get("timestamp", 0) >= start]
 
-                if not d:
-                    alert(screen, "Update failed")
-                    time.sleep(0.5)
+        sort = STATE.get("sort", {}).get(CURRENT["category"], "newest")
+
+        if sort == "oldest":
+            entries = entries[::-1]
+        elif sort == "source":
+            entries = sorted(entries, key=lambda entry: entry.get("sourceName", "").lower())
+        elif sort == "title":
+            entries = sorted(entries, key=lambda entry: entry.get("title", "").lower())
+
+        if CONFIG["mode"] == "group":
+            groups = {}
+            for entry in entries:
+                groups.setdefault(entry.get("sourceName", ""), []).append(entry)
+
+            collapsed = CURRENT.setdefault("collapsed", {}).get(CURRENT["category"], set())
+
+            entries = []
+            for source, items in groups.items():
+                entries.append({"id": "group:" + source, "group": source, "sourceName": "%s %s (%d)" % ("+" if source in collapsed else "-", source, len(items))})
+                if source not in collapsed:
+                    entries += items
+
+        CURRENT["entries"] = entries
+        CONFIG["rowlimit"] = len(entries)
+
+        # A filter can leave fewer entries than the selected line, or none at all
+
+        if CURRENT.get("line", -1) >= len(entries):
+            CURRENT["line"] = -1
+        CURRENT["offset"] = max(min(CURRENT.get("offset", 0), CONFIG["rowlimit"] - pageSize()), 0)
+
+    def timeRange():
+
+        # Hours of entries shown in the current category: set with [ and ], else "range" from feeds.json or config.json
+
+        return STATE.get("range", {}).get(CURRENT["category"], feedOption(CURRENT["category"], None, "range", 0))
+
+    def listArea():
+
+        # width and number of rows left to the entry list; the preview pane goes right on wide terminals, below otherwise
+
+        rows = screen.height - (2 if CONFIG["accessible"] else 1)
+
+        if not CONFIG["preview"]:
+            return screen.width, rows
+
+        if screen.width >= 120:
+            return screen.width // 2, rows
+
+        return screen.width, rows // 2
+
+    def columnCount():
+
+        # "columns" splits the list into up to SETTINGS["columns"] columns at least 60 characters wide
+
+        if CONFIG["mode"] != "columns":
+            return 1
+
+        return max(1, min(SETTINGS.get("columns", 2), listArea()[0] // 60))
+
+    def pageSize():
+
+        return listArea()[1] * columnCount() - extraRows()
+
+    def cellOf(i):
+
+        # left edge, row and width of the i-th entry, counted from the first one on screen
+
+        width, rows = listArea()
+        width //= columnCount()
+
+        # Entries below an expanded title are pushed down
+
+        extra = extraRows() if CURRENT.get("offset", 0) <= CURRENT["line"] < i else 0
+
+        i -= CURRENT.get("offset", 0)
+
+        return i // rows * width, i % rows + 1 + extra, width
+
+    def isVisible(i):
+
+        return 0 <= i - CURRENT.get("offset", 0) < pageSize()
+
+    def scrollTo(line):
+
+        # Moves the viewport so the line is on screen; True when it moved
+
+        offset = CURRENT.get("offset", 0)
+
+        if line < offset:
+            CURRENT["offset"] = line
+        elif line >= offset + pageSize():
+            CURRENT["offset"] = line - pageSize() + 1
+
+        return CURRENT["offset"] != offset
+
+    def checkRefresh():
+
+        # Keep every category warm in the background, one fetch at a time, current category first
+
+        if not CONFIG["autoRefresh"]:
+            return
+
+        for ccategory in [CURRENT["category"]] + [d[0] for d in CONFIG["categories"]]:
+
+            if ccategory not in data:
+                data[ccategory] = readCache(ccategory) or {"entries": [], "created_at": 0}
+
+            if ccategory in FETCHING or int(data[ccategory].get("created_at", 0)) + refreshInterval(ccategory) >= int(time.time()):
+                continue
+
+            if ccategory != CURRENT["category"] and FETCHING:
+                return
+
+            fetchInBackground(ccategory)
+
+    def mergeFetched():
+
+        while not FETCHED.empty():
+
+            ccategory, d = FETCHED.get()
+
+            FETCHING.discard(ccategory)
+            PROGRESS.pop(ccategory, None)
+            CURRENT.pop("loading", None)
+
+            if not d:
+                if ccategory in data:
                     data[ccategory]["created_at"] = int(time.time())
-                    return
+                if ccategory == CURRENT["category"]:
+                    drawCategories()
+                    drawEntries(force=True)
+                    alert(screen, "Update failed")
+                continue
 
-                data[ccategory] = d
+            data[ccategory] = d
 
-                if ccategory != CURRENT["category"]:
-                    return
+            mergeRead(d.get("read", []))
 
-                if CURRENT["line"] > -1:
-                    i = -1
-                    for entry in data[ccategory]["entries"]:
-                        i += 1
-                        if entry["id"] == CURRENT["id"]:
-                            CURRENT["line"] = i
-                            break
-                    CURRENT["line"] = i
+            if ccategory != CURRENT["category"]:
+                continue
 
-                drawCategories()
-                drawEntries(force=True)
-                screen.refresh()
+            updateView()
 
-    def isDoubleChar(s):
+            if CURRENT["line"] > -1:
+                i = -1
+                for entry in CURRENT["entries"]:
+                    i += 1
+                    if entry["id"] == CURRENT.get("id"):
+                        CURRENT["line"] = i
+                        break
+                CURRENT["line"] = i
+
+            if CURRENT["line"] >= CONFIG["rowlimit"]:
+                CURRENT["line"] = CONFIG["rowlimit"] - 1
+
+            scrollTo(max(CURRENT["line"], 0))
 
-        return (
-            re.compile(
-                "(\u00a9|\u00ae|[\u2000-\u3300]|\ud83c[\ud000-\udfff]|\ud83d[\ud000-\udfff]|\ud83e[\ud000-\udfff]|[가-힣]|[\u4e00-\u9fff]|[\u3400-\u4dbf]|[\U00020000-\U0002a6df]|[\U0002a700-\U0002b73f]|[\U0002b740-\U0002b81f]|[\U0002b820-\U0002ceaf])"
-            ).findall(s)
-            != []
-        )
+            drawCategories()
+            drawEntries(force=True)
+            screen.refresh()
+
+    def clearScreen():
 
-    def textLength(s):
+        # The terminal's own background is black or whatever it is set to, so other backgrounds are painted
+
+        screen.clear()
 
-        return sum([2 if isDoubleChar(d) else 1 for d in s])
+        if COLOR["background"]:
+            for y in range(screen.height):
+                screen.print_at(" " * screen.width, 0, y, colour=COLOR["background"], bg=COLOR["background"])
 
     def alert(screen, text):
 
@@ -256,51 +682,164 @@ def layout(screen):
 
             x += len(s) + 2
 
+        status = [(" %s " % SORTS[STATE.get("sort", {}).get(CURRENT["category"], "newest")], COLOR["categoryfg"], COLOR["categorybg"])]
+
+        if CONFIG["hideRead"]:
+            status.append((" Unread ", COLOR["categoryfg"], COLOR["categorybg"]))
+
+        if CONFIG["today"]:
+            status.append((" Today ", COLOR["categoryfg"], COLOR["categorybg"]))
+
+        if timeRange():
+            status.append((" Last %s " % ("%dd" % (timeRange() // 24) if timeRange() % 24 == 0 else "%dh" % timeRange()), COLOR["categoryfg"], COLOR["categorybg"]))
+
+        if downloadStatus():
+            status.append((" %s " % downloadStatus(), COLOR["categoryfg"], COLOR["categorybg"]))
+
+        if CURRENT.get("source"):
+            status.append((" %s " % CURRENT["source"], COLOR["alertfg"], COLOR["alertbg"]))
+
+        if CURRENT.get("search") or CURRENT.get("query"):
+            status.append((" %s%s%s " % ("All: " if CURRENT.get("searchAll") else "/", CURRENT.get("query", ""), "_" if CURRENT.get("search") else ""), COLOR["alertfg"], COLOR["alertbg"]))
+
+        x = screen.width
+        for s, fg, bg in status:
+            x -= textLength(s)
+            screen.print_at(s, x, 0, colour=fg, bg=bg)
+
+    def drawLoading(force=False):
+
+        # A spinner and the feed being fetched, while the current category loads
+
+        if CURRENT["category"] not in FETCHING:
+            return
+
+        n, total, source = PROGRESS.get(CURRENT["category"], (0, 0, ""))
+        text = "%s %s" % (SPINNER[int(time.time() * 10) % len(SPINNER)], "fetching %d/%d: %s" % (n, total, source) if total else "LOADING")
+
+        if text == CURRENT.get("loading") and not force:
+            return
+
+        if len(text) < len(CURRENT.get("loading", "")):
+            drawCategories()
+
+        CURRENT["loading"] = text
+        alert(screen, text)
+
+        # and a bar on the last row, filled up to the feed being fetched
+
+        if total and not CONFIG["accessible"]:
+            filled = screen.width * n // total
+            screen.print_at("\u2501" * filled, 0, screen.height - 1, colour=COLOR["alertbg"], bg=COLOR["background"])
+            screen.print_at("\u2500" * (screen.width - filled), filled, screen.height - 1, colour=COLOR["time"], bg=COLOR["background"])
+            screen.refresh()
+
+    def sourceColor(entry, default):
+
+        # "color" on a feed or a category in feeds.json, as a color number or "#rrggbb"
+
+        try:
+            return toIndex(feedOption(CURRENT["category"], entry.get("source"), "color", default), CONFIG["color"])
+        except (TypeError, ValueError):
+            return default
+
+    def fieldText(entry, key):
+
+        txt = entry.get(key, "")
+
+        if key == "pubDate" and "timestamp" in entry:
+            txt = formatTime(entry["timestamp"])
+
+        if key == "sourceName" and feedOption(CURRENT["category"], entry.get("source"), "icon"):
+            txt = "%s %s" % (feedOption(CURRENT["category"], entry.get("source"), "icon"), txt)
+
+        if key == "title" and entry.get("enclosure"):
+            txt = "\u266a " + txt
+
+        if key == "pubDate" and "points" in entry:
+            txt = "%d pts %d comments  %s" % (entry["points"], entry.get("comments", 0), txt)
+
+        return txt
+
+    def expandedTitle(i):
+
+        # With "marquee": "expand", the selected title is wrapped over up to 3 rows, pushing the rest down, instead of scrolling
+
+        if not CONFIG["expand"] or i != CURRENT["line"] or columnCount() > 1 or CURRENT.get("input") or i >= len(CURRENT["entries"]):
+            return None
+
+        entry = CURRENT["entries"][i]
+
+        if "group" in entry:
+            return None
+
+        col = [f[0] for f in FIELDS["default"] if f[1] == "title"][0]
+        wrapped = wrapText(fieldText(entry, "title"), max(listArea()[0] - col - textLength(fieldText(entry, "pubDate")) - 3, 10))
+
+        if len(wrapped) > 3:
+            wrapped = wrapped[:2] + [truncateText(wrapped[2], textLength(wrapped[2]) - 1) + "\u2026"]
+
+        return wrapped
+
+    def extraRows():
+
+        wrapped = expandedTitle(CURRENT["line"]) if CURRENT.get("line", -1) > -1 else None
+
+        return len(wrapped) - 1 if wrapped else 0
+
     def drawEntries(clearline=False, force=False, lines=False):
 
         category_ = CURRENT["category"]
 
+        # Rows move with the expanded title, so everything is drawn again
+
+        if CONFIG["expand"] and not lines:
+            force = True
+
         if category_ not in FIELDS:
             category_ = "default"
 
-        lineRange = range(0, CONFIG["rowlimit"])
+        offset = CURRENT.get("offset", 0)
+        lineRange = range(offset, min(offset + pageSize(), CONFIG["rowlimit"]))
 
         if lines:
-            lineRange = range(0, lines)
+            lineRange = range(offset, offset + lines)
 
         elif CURRENT["line"] > -1 and not force:
             lineRange = [CURRENT["line"]]
-            if CURRENT["oline"] != CURRENT["line"] and CURRENT["oline"] != -1:
+            if CURRENT["oline"] != CURRENT["line"] and CURRENT["oline"] != -1 and isVisible(CURRENT["oline"]):
                 lineRange = [CURRENT["oline"], CURRENT["line"]]
 
         for i in lineRange:
             isSelected = (i == CURRENT["line"]) and not CURRENT.get("input", False)
-            row = i + 1
+            x, row, width = cellOf(i)
 
             if isSelected:
                 screen.print_at(
-                    " " * screen.width,
-                    0,
+                    " " * width,
+                    x,
                     row,
                     colour=COLOR["selected"],
                     bg=COLOR["selected"],
                 )
             else:
-                screen.print_at(" " * screen.width, 0, row, colour=0, bg=0)
+                screen.print_at(" " * width, x, row, colour=COLOR["background"], bg=COLOR["background"])
 
             if CURRENT["line"] > -1 and clearline and not force and not isSelected:
                 screen.refresh()
 
+            wrapped = expandedTitle(i) if isSelected else None
+
             for f in FIELDS[category_]:
                 kColor = 2 if len(f) > 2 else 1
 
-                txt = data[CURRENT["category"]]["entries"][i].get(f[1], "")
+                txt = fieldText(CURRENT["entries"][i], f[1])
 
-                if isSelected and f[1] + "S" in data[CURRENT["category"]]["entries"][i]:
-                    txt = data[CURRENT["category"]]["entries"][i][f[1] + "S"]
-                    if f[1] in data[CURRENT["category"]]["entries"][i] and len(data[CURRENT["category"]]["entries"][i][f[1]]) > len(txt):
+                if isSelected and f[1] + "S" in CURRENT["entries"][i]:
+                    txt = CURRENT["entries"][i][f[1] + "S"]
+                    if f[1] in CURRENT["entries"][i] and len(CURRENT["entries"][i][f[1]]) > len(txt):
 
-                        txt += " " * (len(data[CURRENT["category"]]["entries"][i][f[1]]) - len(txt))
+                        txt += " " * (len(CURRENT["entries"][i][f[1]]) - len(txt))
 
                 if txt == "":
                     continue
@@ -308,24 +847,29 @@ def layout(screen):
                 col = f[0]
 
                 if col < 0:
-                    col = screen.width + col - len(txt)
+                    col = width + col - len(txt)
                 elif CURRENT.get("input", False):
-                    col += 4
+                    col += numberWidth() + 1
 
                 fg = COLOR.get(f[kColor], COLOR["default"])
-                bg = 0
+                bg = COLOR["background"]
+
+                if f[1] == "sourceName":
+                    fg = sourceColor(CURRENT["entries"][i], fg)
 
                 if i == CURRENT["line"] and not CURRENT.get("input", False):
-                    fg = 0
+                    fg = COLOR["selectedfg"]
                     bg = COLOR["selected"]
                     if COLOR.get("%sS" % f[kColor], None):
                         fg = COLOR["%sS" % f[kColor]]
 
-                if isSelected and f[1] in CONFIG["marqueeFields"]:
+                if wrapped and f[1] == "title":
+                    txt = wrapped[0]
+                elif isSelected and f[1] in CONFIG["marqueeFields"]:
                     txt = sliceText(
                         txt,
-                        screen.width - col - 1,
-                        maxwidth=screen.width - col,
+                        width - col - 1,
+                        maxwidth=width - col,
                         shift=CURRENT["shift"],
                     )
 
@@ -333,23 +877,117 @@ def layout(screen):
                     col -= 1
                     txt = " %s " % txt
 
-                if len(f) > 3:
+                if len(f) > 3 and not CONFIG["accessible"]:
                     txt += " " * 20
 
                 try:
-                    screen.print_at(txt, col, row, colour=fg, bg=bg)
+                    screen.print_at(truncateText(txt, width - col), x + col, row, colour=fg, bg=bg)
                 except:
                     pass
 
+            for k, line in enumerate(wrapped[1:] if wrapped else []):
+                screen.print_at(" " * width, x, row + k + 1, colour=COLOR["selected"], bg=COLOR["selected"])
+                screen.print_at(line, x + [f[0] for f in FIELDS["default"] if f[1] == "title"][0], row + k + 1, colour=COLOR.get("titleS", COLOR["selectedfg"]), bg=COLOR["selected"])
+
             if CURRENT["line"] > -1 and clearline and not force and not isSelected:
                 screen.refresh()
 
-        if force and lineRange[-1] + 1 < screen.height - 1:
-            for i in range(lineRange[-1] + 2, screen.height):
-                screen.print_at(" " * screen.width, 0, i, colour=0, bg=0)
+        if force:
+            for i in range(lineRange[-1] + 1 if len(lineRange) else offset, offset + pageSize()):
+                x, row, width = cellOf(i)
+                screen.print_at(" " * width, x, row, colour=COLOR["background"], bg=COLOR["background"])
+
+        drawPreview(force=force)
+        announce()
 
+        if force:
             screen.refresh()
 
+    def announce():
+
+        # In accessible mode the selected entry is written out on the last row, which screen readers can follow,
+        # and appended to the file or FIFO named by "announce" in config.json
+
+        if not CONFIG["accessible"]:
+            return
+
+        if CURRENT["line"] > -1 and CURRENT["line"] < len(CURRENT["entries"]) and "group" not in CURRENT["entries"][CURRENT["line"]]:
+            entry = CURRENT["entries"][CURRENT["line"]]
+            text = "%d of %d. %s: %s. %s" % (CURRENT["line"] + 1, len(CURRENT["entries"]), entry.get("sourceName", ""), entry.get("title", ""), fieldText(entry, "pubDate"))
+        else:
+            text = "%s, %d entries" % (dict(CONFIG["categories"]).get(CURRENT["category"], ""), len(CURRENT["entries"]))
+
+        screen.print_at(truncateText(text, screen.width).ljust(screen.width), 0, screen.height - 1, colour=COLOR["default"], bg=COLOR["background"])
+
+        if text == CURRENT.get("announced") or not SETTINGS.get("announce"):
+            return
+
+        CURRENT["announced"] = text
+
+        # Opened without blocking, so nothing waits when no one reads the FIFO
+
+        try:
+            fd = os.open(os.path.expanduser(SETTINGS["announce"]), os.O_WRONLY | os.O_APPEND | os.O_CREAT | os.O_NONBLOCK)
+            os.write(fd, (text + "\n").encode("utf-8"))
+            os.close(fd)
+        except:
+            pass
+
+    def drawPreview(force=False):
+
+        if not CONFIG["preview"]:
+            return
+
+        entry = CURRENT["entries"][CURRENT["line"]] if CURRENT["line"] > -1 else {}
+
+        if not force and CURRENT.get("previewId") == entry.get("id"):
+            return
+
+        CURRENT["previewId"] = entry.get("id")
+
+        width, rows = listArea()
+
+        if width < screen.width:
+            x, y, w, h = width + 2, 1, screen.width - width - 3, screen.height - 1
+            for row in range(1, screen.height):
+                screen.print_at(" ", width, row, colour=COLOR["categorybg"], bg=COLOR["categorybg"])
+        else:
+            x, y, w, h = 1, rows + 2, screen.width - 2, screen.height - rows - 2
+            screen.print_at(" " * screen.width, 0, rows + 1, colour=COLOR["categorybg"], bg=COLOR["categorybg"])
+
+        for i in range(h):
+            screen.print_at(" " * w, x, y + i, colour=COLOR["background"], bg=COLOR["background"])
+
+        if not entry or "group" in entry:
+            return
+
+        if SETTINGS.get("images"):
+            loadImageInBackground(screen, entry, min(SETTINGS.get("imageHeight", 10), h // 2))
+
+            if IMAGES[entry["id"]]:
+                text, colours = IMAGES[entry["id"]]
+                for row, line in enumerate(text[:h]):
+                    for col, d in enumerate(line[:w]):
+                        fg, attr, bg = colours[row][col]
+                        screen.print_at(d, x + col, y + row, colour=fg, attr=attr, bg=bg)
+
+                y += len(text[:h]) + 1
+                h -= len(text[:h]) + 1
+
+        lines = [(d, COLOR["default"]) for d in wrapText(entry.get("title", ""), w)]
+        lines += [("%s  %s" % (entry.get("sourceName", ""), formatTime(entry["timestamp"])), COLOR["source"]), ("", 0)]
+        lines += [(d, COLOR["default"]) for d in wrapText(readArticle(entry) or entry.get("description", ""), w)]
+
+        for i, (line, fg) in enumerate(lines[: max(h, 0)]):
+            screen.print_at(line, x, y + i, colour=fg, bg=COLOR["background"])
+
+    def mergeImages():
+
+        while not IMAGED.empty():
+            if IMAGED.get() == CURRENT.get("previewId"):
+                drawPreview(force=True)
+                screen.refresh()
+
     def doTimer():
         if CURRENT["line"] > -1:
             CURRENT["shift"] = CURRENT.get("shift", 0) + (1 if CURRENT.get("direction", "left") == "left" else -1)
@@ -360,9 +998,16 @@ def layout(screen):
         CURRENT["shift"] = 0
         CURRENT["oline"] = CURRENT["line"]
 
+    def numberWidth():
+
+        # digits of the numbers shown with [:], at least 3
+
+        return max(len(str(CONFIG["rowlimit"])), 3)
+
     def showCurrentInputNumber():
 
-        lineRange = range(0, CONFIG["rowlimit"])
+        offset = CURRENT.get("offset", 0)
+        lineRange = range(offset, min(offset + pageSize(), CONFIG["rowlimit"]))
 
         try:
             currentNumber = int(CURRENT["inputnumber"])
@@ -373,7 +1018,8 @@ def layout(screen):
             fg = COLOR["number"]
             if i + 1 == currentNumber:
                 fg = COLOR["numberselected"]
-            screen.print_at(("%3s" % (i + 1)).rjust(3), 1, i + 1, colour=fg, bg=0)
+            x, row, width = cellOf(i)
+            screen.print_at(str(i + 1).rjust(numberWidth()), x + 1, row, colour=fg, bg=COLOR["background"])
 
         screen.refresh()
 
@@ -385,31 +1031,100 @@ def layout(screen):
         drawEntries(clearline=True, force=True)
         screen.refresh()
 
-    def openURL(cn):
+    def toggleGroup(expand=None):
 
-        if "link" in cn:
-            webbrowser.open(cn["link"], new=2)
-        elif "url" in cn:
-            webbrowser.open(cn["url"], new=2)
-        elif "links" in cn:
-            if len(cn["links"]) == 1:
-                webbrowser.open(cn["links"][0], new=2)
-            else:
-                webbrowser.open(cn["permalink"], new=2)
-        elif "permalink" in cn:
-            webbrowser.open(cn["permalink"], new=2)
-        else:
+        if CONFIG["mode"] != "group" or CURRENT["line"] < 0:
             return False
 
-        return True
+        cn = CURRENT["entries"][CURRENT["line"]]
+        source = cn.get("group", cn.get("sourceName", ""))
 
-    def showHelp():
+        collapsed = CURRENT.setdefault("collapsed", {}).setdefault(CURRENT["category"], set())
+
+        if expand is None:
+            expand = source in collapsed
+
+        if expand:
+            collapsed.discard(source)
+        else:
+            collapsed.add(source)
+
+        resetListArrowKey()
+        updateView()
+
+        for i, entry in enumerate(CURRENT["entries"]):
+            if entry.get("group") == source:
+                CURRENT["line"] = i
+                break
+
+        drawEntries(force=True)
+        screen.refresh()
+
+        return True
+
+    def openURL(cn):
+
+        if "link" in cn:
+            url = cn["link"]
+        elif "url" in cn:
+            url = cn["url"]
+        elif "links" in cn:
+            url = cn["links"][0] if len(cn["links"]) == 1 else cn["permalink"]
+        elif "permalink" in cn:
+            url = cn["permalink"]
+        else:
+            return False
+
+        if "id" in cn and remoteServer():
+            mergeRead([cn["id"]])
+            runInBackground(markRemoteRead, cn["id"])
+        elif "id" in cn:
+            markRead(cn["id"])
+            if SETTINGS.get("feedbin"):
+                runInBackground(markFeedbinRead, cn["id"])
+            if SETTINGS.get("nextcloud"):
+                runInBackground(markNextcloudRead, cn["id"])
+
+        recordOpen(CURRENT["category"], cn.get("sourceName", ""))
+
+        return openLink(url, CURRENT["category"], cn.get("source", cn.get("sourceName")))
+
+    def showHelp():
         w = 60
         s = """
             [Up], [Down], [W], [S], [J], [K] : Select from list
 [Shift]+[Up], [Shift]+[Down], [PgUp], [PgDn] : Quickly select from list
                                          [O] : Open canonical link
+                                         [C] : Open the comments (Reddit, Hacker News)
+                                         [Y] : Copy the link to the clipboard
+                                         [M] : Copy as a Markdown link
+                                 [Shift]+[M] : Save as a Markdown note
+                                         [L] : Save to Wallabag, Pocket or Instapaper
+                                         [B] : Share to Mastodon or Bluesky
+                                         [@] : Send by mail
+                                         [A] : Play the podcast episode
+                                         [D] : Download the podcast episode
+                                 [Shift]+[D] : Show downloads
+                                         [E] : Show feeds that failed to update
+                                         [#] : Show reading statistics
+                                         [X] : Export the listed entries to CSV or HTML
                                          [:] : Select by typing a number from list
+                                         [G] : Change the sort order
+                                         [U] : Show only unread entries, or all
+                                         [F] : Show only today's entries, or all
+                                    [[], []] : Show entries from a shorter/longer time
+                                    [+], [-] : Refresh the category more/less often
+                                         [V] : Change the layout (list, grouped, columns)
+                             [Left], [Right] : Collapse/expand a group, or move between columns
+                                         [P] : Show the selected entry in a preview pane
+                                         [I] : Show details; [O] open, [Y] copy, [S] save, [R] refetch
+                                         [!] : Show the raw fields of the selected entry
+                                         [T] : Switch to the next color theme
+                                         [/] : Search headlines, [Esc] to clear
+                                  [Ctrl]+[F] : Search all categories and past entries
+                                  [Ctrl]+[T] : Find an entry by fuzzy matching
+                                  [Ctrl]+[P] : Go to a category, or a single feed
+                            [N], [Shift]+[N] : Next/previous match
                         [Tab], [Shift]+[Tab] : Change the category tab
                              [Q], [Ctrl]+[C] : Quit
 """
@@ -418,7 +1133,7 @@ def layout(screen):
         lines = len(s)
         width = max([len(d) for d in s]) + 2
 
-        screen.clear()
+        clearScreen()
         top = int(screen.height / 2 - lines / 2)
         left = int(screen.width / 2 - width / 2)
         for i, d in enumerate(s):
@@ -438,27 +1153,428 @@ def layout(screen):
                 return
             time.sleep(0.5)
 
-        screen.clear()
+        clearScreen()
 
-    reloadLoop = threading.Thread(target=reloadData, args=[])
-    reloadLoop.daemon = True
-    reloadLoop.start()
+    def drawPopup(s):
 
-    CURRENT = {"line": -1, "column": -1, "category": "news"}
+        width = max([textLength(d) for d in s]) + 4
+        top = max(int(screen.height / 2 - len(s) / 2), 1)
+        left = max(int(screen.width / 2 - width / 2), 0)
 
-    data[CURRENT["category"]] = getFeed(CURRENT["category"])
+        clearScreen()
+        for i, d in enumerate(s[: screen.height - 1]):
+            screen.print_at(" " * width, left, top + i, colour=COLOR["alertfg"], bg=COLOR["alertbg"])
+            screen.print_at(d, left + 2, top + i, colour=COLOR["alertfg"], bg=COLOR["alertbg"])
+
+        screen.refresh()
+
+    def showDownloads():
+
+        # Redrawn every half second until a key is pressed
+
+        while True:
+            s = ["", "Downloads", ""]
+
+            for d in DOWNLOADS:
+                s.append("%-10s %s" % (downloadProgress(d), truncateText(d["title"] or d["url"], screen.width - 20)))
+                s.append("%-10s %s" % ("", truncateText(d.get("error", d["path"]) if d["status"] == "failed" else d["path"], screen.width - 20)))
+
+            if not DOWNLOADS:
+                s.append("No downloads yet. Press [D] on a podcast entry")
+
+            s.append("")
+
+            drawPopup(s)
+
+            for i in range(10):
+                if screen.get_key():
+                    clearScreen()
+                    return
+                time.sleep(0.05)
+
+    def showErrors():
+
+        # Errors of the last fetch of every category, from the cache
+
+        s = ["", "Feeds that failed to update", ""]
+
+        for key, title in CONFIG["categories"]:
+            for source, d in (readCache(key) or {}).get("errors", {}).items():
+                s.append("%s / %s" % (title, source))
+                s.append("    " + truncateText(d["error"], screen.width - 12))
+                s.append("    " + truncateText(d["url"], screen.width - 12))
+
+        if len(s) == 3:
+            s.append("All feeds were updated")
 
-    CONFIG["rowlimit"] = screen.height - 2
+        s.append("")
+
+        drawPopup(s)
+
+        while not screen.get_key():
+            time.sleep(0.05)
+
+        clearScreen()
+
+    def showStats():
+
+        # Entries opened per day over the last week, the most read sources and the busiest categories
+
+        opened = STATE.get("opened", [])
+        today = datetime.datetime.now(timezone()).replace(hour=0, minute=0, second=0, microsecond=0)
+        titles = dict(CONFIG["categories"])
+
+        s = ["", "Opened in the last 7 days", ""]
+
+        for i in range(6, -1, -1):
+            start = (today - datetime.timedelta(days=i)).timestamp()
+            count = len([d for d in opened if start <= d[0] < start + 86400])
+            s.append("%-12s %4d %s" % ((today - datetime.timedelta(days=i)).strftime("%a %b %d"), count, "#" * min(count, 40)))
+
+        def top(counts):
+            return sorted(counts.items(), key=lambda d: -d[1])[:5]
+
+        sources, categories, fetched = {}, {}, {}
+
+        for d in opened:
+            sources[d[2]] = sources.get(d[2], 0) + 1
+            categories[titles.get(d[1], d[1])] = categories.get(titles.get(d[1], d[1]), 0) + 1
+
+        for key, title in CONFIG["categories"]:
+            fetched[title] = len([entry for entry in (data.get(key) or {"entries": []})["entries"] if entry.get("timestamp", 0) >= time.time() - 86400])
+
+        s += ["", "Most read sources", ""] + ["%-30s %4d" % (truncateText(name, 30), count) for name, count in top(sources)]
+        s += ["", "Most read categories", ""] + ["%-30s %4d" % (truncateText(name, 30), count) for name, count in top(categories)]
+        s += ["", "New entries in the last 24 hours", ""] + ["%-30s %4d" % (truncateText(name, 30), count) for name, count in top(fetched) if count]
+        s.append("")
+
+        drawPopup(s)
+
+        while not screen.get_key():
+            time.sleep(0.05)
+
+        clearScreen()
+
+    def showDetail(entry, raw=False):
+
+        # The entry as the feed has it, scrolled with the arrow keys until another key is pressed;
+        # with raw, the fields the feed gave for it before they were formatted
+
+        w, h = screen.width - 8, screen.height - 3
+        at = datetime.datetime.fromtimestamp(entry["timestamp"], timezone())
+
+        def build():
+
+            if raw:
+                lines = [(d, COLOR["alertfg"]) for d in wrapText(entry.get("title", ""), w)]
+
+                if "raw" not in entry:
+                    lines += [("", 0), ("Refresh the category to see every field", COLOR["time"])]
+
+                for key, value in dict(entry.get("raw", {}), id=entry.get("id"), url=entry.get("url"), timestamp=entry.get("timestamp")).items():
+                    lines += [("", 0), (key, COLOR["source"])]
+                    for d in value if isinstance(value, list) else [value]:
+                        lines += [("  " + line, COLOR["default"]) for line in wrapText(str(d), w - 2)]
+
+                return lines, "\n".join(d[0] for d in lines), []
+
+            # The title and the links are OSC 8 hyperlinks in terminals that support them
+
+            lines = [(d, COLOR["alertfg"], entry.get("url")) for d in wrapText(entry.get("title", ""), w)] + [("", 0)]
+            lines += [(d, COLOR["source"]) for d in wrapText(entry.get("sourceName", "") + (" / " + entry["author"] if entry.get("author") not in ("", None, entry.get("sourceName")) else ""), w)]
+            lines += [(at.strftime("%A, %B %d, %Y %H:%M %Z").strip(), COLOR["time"])]
+
+            if entry.get("tags"):
+                lines += [(d, COLOR["time"]) for d in wrapText(", ".join(entry["tags"]), w)]
+
+            content = readArticle(entry) or entry.get("description", "")
+
+            lines += [("", 0)] + [(d, COLOR["default"]) for d in wrapText(content, w)]
+
+            # Links of the article or the feed's content, opened by typing their number
+
+            links = articleLinks(entry) or entry.get("contentLinks", [])
+
+            if links:
+                lines += [("", 0), ("Links, type a number to open", COLOR["time"])]
+                lines += [(truncateText("%3d  %s" % (i + 1, link), w), COLOR["number"], link) for i, link in enumerate(links)]
+
+            return lines, content, links
+
+        lines, content, links = build()
+        top, number, message = 0, "", None
+
+        while True:
+            clearScreen()
+            for i, (line, fg, *url) in enumerate(lines[top : top + h]):
+                screen.print_at(line, 4, i + 2, colour=fg, bg=COLOR["background"])
+
+            # Scrollbar at the right edge and the lines shown, when the text is longer than the screen
+
+            if len(lines) > h:
+                size = max(h * h // len(lines), 1)
+                start = top * (h - size) // max(len(lines) - h, 1)
+                for i in range(h if not CONFIG["accessible"] else 0):
+                    screen.print_at("█" if start <= i < start + size else "│", screen.width - 2, i + 2, colour=COLOR["time"], bg=COLOR["background"])
+                status = " lines %d-%d of %d " % (top + 1, min(top + h, len(lines)), len(lines))
+                screen.print_at(status, screen.width - len(status) - 3, screen.height - 1, colour=COLOR["time"], bg=COLOR["background"])
+
+            if number or message:
+                screen.print_at(" %s " % ("Open link %s_" % number if number else message), 4, screen.height - 1, colour=COLOR["alertfg"], bg=COLOR["alertbg"])
+            screen.refresh()
+
+            if hyperlinksSupported():
+                drawHyperlinks([(4, i + 2, line, url[0], fg, COLOR["background"]) for i, (line, fg, *url) in enumerate(lines[top : top + h]) if url and url[0]])
+
+            message = None
+
+            keyCode = screen.get_key()
+
+            while keyCode is None:
+                time.sleep(0.05)
+                keyCode = screen.get_key()
+
+            if keyCode == KEY["down"] or keyCode in KEY["j"]:
+                top = min(top + 1, max(len(lines) - h, 0))
+            elif keyCode == KEY["up"] or keyCode in KEY["k"]:
+                top = max(top - 1, 0)
+            elif keyCode in [KEY["pageDown"], KEY["space"]]:
+                top = min(top + h, max(len(lines) - h, 0))
+            elif keyCode == KEY["pageUp"]:
+                top = max(top - h, 0)
+            elif keyCode == KEY["g"][0]:
+                top = 0
+            elif keyCode == KEY["g"][1]:
+                top = max(len(lines) - h, 0)
+            elif keyCode in KEYLIST["number"] and links:
+                number = (number + chr(keyCode)).lstrip("0")
+                if number and int(number) * 10 > len(links):  # no more digits can follow
+                    keyCode = KEY["enter"]
+            elif keyCode == KEY["backspace"] and number:
+                number = number[:-1]
+            elif keyCode in KEY["o"] and entry.get("url"):
+                openURL(entry)
+                message = "Opened"
+            elif keyCode in KEY["r"] and entry.get("url"):
+
+                # The page again, when the saved text caught a cookie wall or was cut short
+
+                screen.print_at(" Fetching the page ", 4, screen.height - 1, colour=COLOR["alertfg"], bg=COLOR["alertbg"])
+                screen.refresh()
+
+                try:
+                    saveArticle(entry)
+                    lines, content, links = build()
+                    top, message = min(top, max(len(lines) - h, 0)), "Fetched the page again"
+                except Exception as e:
+                    message = "Fetch failed: %s" % e
+
+            elif keyCode in KEY["s"] and entry.get("url"):
+                try:
+                    message = "Saved to %s" % os.path.basename(saveNote(entry, CURRENT["category"]))
+                except OSError as e:
+                    message = "Cannot save note: %s" % e.strerror
+            elif keyCode in KEY["y"]:
+                copyToClipboard("%s\n%s\n\n%s\n" % (entry.get("title", ""), entry.get("url", ""), content))
+                message = "Copied the text"
+            elif keyCode != KEY["enter"] or not number:
+                clearScreen()
+                return
 
-    if len(data[CURRENT["category"]]["entries"]) < CONFIG["rowlimit"]:
-        CONFIG["rowlimit"] = len(data[CURRENT["category"]]["entries"])
+            if keyCode == KEY["enter"] and number:
+                if int(number) <= len(links):
+                    openLink(links[int(number) - 1], CURRENT["category"], entry.get("source", entry.get("sourceName")))
+                number = ""
 
-    if CONFIG["rowlimit"] > 999:
-        CONFIG["rowlimit"] = 999
+    def rememberPlace():
 
-    screen.clear()
+        place = {"id": CURRENT.get("id") if CURRENT["line"] > -1 else None, "offset": CURRENT.get("offset", 0)}
+
+        PLACES[CURRENT["category"]] = place
+
+        # With "rememberPlace", also the next time rReader starts
+
+        if SETTINGS.get("rememberPlace"):
+            STATE.setdefault("places", {})[CURRENT["category"]] = place
+            saveState()
+
+    def restorePlace():
+
+        place = PLACES.get(CURRENT["category"]) or (STATE.get("places", {}).get(CURRENT["category"]) if SETTINGS.get("rememberPlace") else None)
+
+        if not place:
+            return
+
+        CURRENT["offset"] = max(min(place["offset"], CONFIG["rowlimit"] - pageSize()), 0)
+
+        for i, entry in enumerate(CURRENT["entries"]):
+            if place["id"] and entry.get("id") == place["id"]:
+                CURRENT["line"], CURRENT["id"], CURRENT["shift"] = i, place["id"], 0
+                scrollTo(i)
+                break
+
+    def switchCategory(category):
+
+        rememberPlace()
+
+        CURRENT["category"] = category
+        data[category] = getFeed(category)
+
+        CURRENT["line"] = -1
+        CURRENT["oline"] = -1
+        CURRENT["offset"] = 0
+        CURRENT["source"] = None
+
+        if CURRENT.get("searchAll"):
+            CURRENT["query"] = ""
+            CURRENT["searchAll"] = False
+            CURRENT["results"] = None
+
+        updateView()
+        restorePlace()
+
+    def selectEntry(category, id):
+
+        if category != CURRENT["category"]:
+            switchCategory(category)
+
+        resetListArrowKey()
+
+        for i, entry in enumerate(CURRENT["entries"]):
+            if entry.get("id") == id:
+                CURRENT["line"] = i
+                scrollTo(i)
+                return True
+
+        return False
+
+    def entryChoices(everywhere):
+
+        # (label, (category, id)) for the entries of the current category, or of all of them
+
+        categories = CONFIG["categories"] if everywhere else [d for d in CONFIG["categories"] if d[0] == CURRENT["category"]]
+        choices = []
+
+        for key, title in categories:
+            for entry in (data.get(key) or readCache(key) or {"entries": []})["entries"]:
+                label = "%s  %s" % (entry.get("sourceName", ""), entry.get("title", ""))
+                choices.append(("%s / %s" % (title, label) if everywhere else label, (key, entry["id"])))
+
+        return choices
+
+    def feedChoices(everywhere):
+
+        # (label, (category, source)) for every category, and every feed to show alone, always from all categories
+
+        choices = []
+
+        for key, title in CONFIG["categories"]:
+            choices.append((title, (key, None)))
+            for source in CONFIG["feeds"].get(key, {}).get("feeds", {}):
+                choices.append(("%s / %s" % (title, source), (key, source)))
+
+        return choices
+
+    def compose(title, lines):
+
+        # One line of text typed under the lines shown; returns it, or None on [Esc]
+
+        text = ""
+
+        while True:
+            width = min(screen.width - 4, 80)
+            s = ["", title, ""] + [truncateText(d, width) for d in lines] + ["", truncateText(text[-(width - 2) :] + "_", width).ljust(width), "", "[Enter] to send, [Esc] to cancel", ""]
+
+            drawPopup(s)
+
+            keyCode = screen.get_key()
+
+            while keyCode is None:
+                time.sleep(0.02)
+                keyCode = screen.get_key()
+
+            if keyCode == KEY["esc"]:
+                return None
+            elif keyCode == KEY["enter"]:
+                return text
+            elif keyCode == KEY["backspace"]:
+                text = text[:-1]
+            elif keyCode >= KEY["space"]:
+                text += chr(keyCode)
+
+    def pick(title, choices, widen=True):
+
+        # Fuzzy picker over choices(everywhere) -> [(label, value)]; [Tab] widens it to every category
+        # when widen is set. Returns the chosen value, or None
+
+        query, line, everywhere = "", 0, False
+        items = choices(everywhere)
+
+        while True:
+            matches = fuzzyRank(query, items, key=lambda d: d[0]) if query else items
+            rows = screen.height - 2
+            line = min(line, max(len(matches) - 1, 0))
+            top = max(line - rows + 1, 0)
+
+            clearScreen()
+            screen.print_at(" " * screen.width, 0, 0, colour=COLOR["categorybg"], bg=COLOR["categorybg"])
+            screen.print_at(" %s%s: %s_ " % (title, " (all)" if everywhere else "", query), 0, 0, colour=COLOR["categoryfgS"], bg=COLOR["categorybgS"])
+            screen.print_at(" %d/%d " % (len(matches), len(items)), screen.width - 12, 0, colour=COLOR["categoryfg"], bg=COLOR["categorybg"])
+
+            for i, (label, value) in enumerate(matches[top : top + rows]):
+                selected = top + i == line
+                screen.print_at(truncateText(" %s " % label, screen.width).ljust(screen.width), 0, i + 1, colour=COLOR["selectedfg"] if selected else COLOR["default"], bg=COLOR["selected"] if selected else COLOR["background"])
+
+            screen.refresh()
+
+            keyCode = screen.get_key()
+
+            while keyCode is None:
+                time.sleep(0.02)
+                keyCode = screen.get_key()
+
+            if keyCode == KEY["esc"]:
+                return None
+            elif keyCode == KEY["enter"]:
+                return matches[line][1] if matches else None
+            elif keyCode == KEY["down"]:
+                line = min(line + 1, len(matches) - 1)
+            elif keyCode == KEY["up"]:
+                line = max(line - 1, 0)
+            elif keyCode == KEY["tab"] and widen:
+                everywhere = not everywhere
+                items = choices(everywhere)
+            elif keyCode == KEY["backspace"]:
+                query, line = query[:-1], 0
+            elif keyCode >= KEY["space"]:
+                query, line = query + chr(keyCode), 0
+
+    # A smaller terminal would garble the list, so wait for it to be resized
+
+    if screen.width < CONFIG["minSize"][0] or screen.height < CONFIG["minSize"][1]:
+        clearScreen()
+        screen.print_at(truncateText("terminal too small (need %dx%d)" % CONFIG["minSize"], screen.width), 0, screen.height // 2, colour=COLOR["default"], bg=COLOR["background"])
+        screen.refresh()
+
+        while not screen.has_resized():
+            time.sleep(0.1)
+            keyCode = screen.get_key()
+            if keyCode == KEY["esc"] or keyCode in KEY["q"]:
+                return True
+
+        return False
+
+    CURRENT = {"line": -1, "column": -1, "shift": 0, "category": CONFIG["category"]}
+
+    data[CURRENT["category"]] = getFeed(CURRENT["category"])
+
+    updateView()
+    restorePlace()
+
+    clearScreen()
     drawCategories()
     drawEntries(force=True)
+    drawLoading(force=True)
     screen.refresh()
 
     currentTime = int(time.time() * CONFIG["marqueeSpeed"])
@@ -467,12 +1583,74 @@ def layout(screen):
 
         time.sleep(0.02)
 
+        mergeFetched()
+        mergeImages()
+        drawLoading()
+
+        while BROKEN:
+            MESSAGES.put(BROKEN.pop(0))
+
+        while not MESSAGES.empty():
+            alert(screen, MESSAGES.get())
+        checkRefresh()
+
+        if CURRENT.get("downloadStatus") != downloadStatus():
+            CURRENT["downloadStatus"] = downloadStatus()
+            drawCategories()
+            screen.refresh()
+
         keyCode = screen.get_key()
 
         if keyCode:
 
+            if CURRENT.get("search"):
+                if keyCode == KEY["enter"]:
+                    CURRENT["search"] = False
+                elif keyCode == KEY["esc"]:
+                    CURRENT["search"] = False
+                    CURRENT["searchAll"] = False
+                    CURRENT["query"] = ""
+                elif keyCode == KEY["backspace"]:
+                    if CURRENT["query"] == "":
+                        CURRENT["search"] = False
+                    CURRENT["query"] = CURRENT["query"][:-1]
+                elif keyCode >= KEY["space"]:
+                    CURRENT["query"] += chr(keyCode)
+                else:
+                    continue
+
+                if CURRENT.get("searchAll"):
+                    CURRENT["results"] = searchIndex(CURRENT["query"]) if CURRENT["query"].strip() else None
+                else:
+                    CURRENT["results"] = None
+
+                resetListArrowKey()
+                CURRENT["offset"] = 0
+                updateView()
+                CURRENT["line"] = 0 if not CURRENT["search"] and CURRENT["query"] and CONFIG["rowlimit"] else -1
+
+                drawCategories()
+                drawEntries(force=True)
+                screen.refresh()
+                continue
+
+            if keyCode == KEY["esc"] and (CURRENT.get("query") or CURRENT.get("source")):
+                CURRENT["query"] = ""
+                CURRENT["searchAll"] = False
+                CURRENT["results"] = None
+                CURRENT["source"] = None
+                CURRENT["line"] = -1
+                resetListArrowKey()
+                updateView()
+
+                drawCategories()
+                drawEntries(force=True)
+                screen.refresh()
+                continue
+
             if keyCode == KEY["esc"] or keyCode in KEY["q"]:
-                screen.clear()
+                rememberPlace()
+                clearScreen()
                 screen.refresh()
                 return True
 
@@ -484,11 +1662,12 @@ def layout(screen):
                     else:
                         CURRENT["line"] = CURRENT["oline"]
 
+                    scrollTo(max(CURRENT["line"], 0))
                     offNumberMode()
                     continue
 
                 elif keyCode in KEYLIST["number"]:
-                    if len(CURRENT["inputnumber"]) < 3:
+                    if len(CURRENT["inputnumber"]) < numberWidth():
                         CURRENT["inputnumber"] += str(keyCode - KEYLIST["number"][0])
 
                 elif keyCode == KEY["backspace"]:
@@ -506,9 +1685,7 @@ def layout(screen):
             elif keyCode in KEY["r"]:
                 CURRENT["line"] = -1
                 data[CURRENT["category"]] = getFeed(CURRENT["category"])
-                CONFIG["rowlimit"] = screen.height - 1
-                if len(data[CURRENT["category"]]["entries"]) < CONFIG["rowlimit"]:
-                    CONFIG["rowlimit"] = len(data[CURRENT["category"]]["entries"])
+                updateView()
                 drawEntries()
                 screen.refresh()
 
@@ -541,11 +1718,219 @@ def layout(screen):
                 if CURRENT["line"] >= CONFIG["rowlimit"]:
                     CURRENT["line"] = 0
 
-            elif keyCode in KEY["o"]:
-                openURL(data[CURRENT["category"]]["entries"][CURRENT["line"]])
+            elif keyCode in [KEY["pageUp"], KEY["pageDown"]]:
+                resetListArrowKey()
+                if keyCode == KEY["pageDown"]:
+                    CURRENT["line"] = min(CURRENT["line"] + pageSize(), CONFIG["rowlimit"] - 1)
+                else:
+                    CURRENT["line"] = max(CURRENT["line"] - pageSize(), 0)
+
+            elif keyCode in [KEY["n"], KEY["N"]] and CURRENT.get("query"):
+                resetListArrowKey()
+                CURRENT["line"] += 1 if keyCode == KEY["n"] else -1
+                if CURRENT["line"] >= CONFIG["rowlimit"]:
+                    CURRENT["line"] = 0
+                elif CURRENT["line"] < 0:
+                    CURRENT["line"] = CONFIG["rowlimit"] - 1
+
+            elif keyCode in [KEY["/"], KEY["ctrlF"]]:
+                CURRENT["search"] = True
+                CURRENT["searchAll"] = keyCode == KEY["ctrlF"]
+                CURRENT["results"] = None
+                CURRENT["query"] = ""
+                CURRENT["line"] = -1
+                resetListArrowKey()
+
+                drawCategories()
+                drawEntries(force=True)
+                screen.refresh()
+
+            elif keyCode in KEY["o"] and -1 < CURRENT["line"] < len(CURRENT["entries"]):
+                openURL(CURRENT["entries"][CURRENT["line"]])
+
+            elif keyCode in [KEY["space"], KEY["enter"]] and CURRENT["line"] > -1 and "group" in CURRENT["entries"][CURRENT["line"]]:
+                toggleGroup()
+
+            elif keyCode in [KEY["left"], KEY["right"]] and CONFIG["mode"] == "columns":
+                resetListArrowKey()
+                line = max(CURRENT["line"], 0) + listArea()[1] * (1 if keyCode == KEY["right"] else -1)
+                if 0 <= line < CONFIG["rowlimit"]:
+                    CURRENT["line"] = line
+                drawEntries(clearline=True)
+                screen.refresh()
+
+            elif keyCode in [KEY["left"], KEY["right"]]:
+                toggleGroup(expand=keyCode == KEY["right"])
+
+            elif keyCode in KEY["c"] and CURRENT["line"] > -1 and CURRENT["entries"][CURRENT["line"]].get("commentsUrl"):
+                cn = CURRENT["entries"][CURRENT["line"]]
+                openLink(cn["commentsUrl"], CURRENT["category"], cn.get("source", cn.get("sourceName")))
+
+            elif keyCode in KEY["y"] and CURRENT["line"] > -1 and CURRENT["entries"][CURRENT["line"]].get("url"):
+                copyToClipboard(CURRENT["entries"][CURRENT["line"]]["url"])
+                alert(screen, "Copied URL")
+
+            elif keyCode == KEY["m"] and CURRENT["line"] > -1 and CURRENT["entries"][CURRENT["line"]].get("url"):
+                cn = CURRENT["entries"][CURRENT["line"]]
+                title = cn.get("title", "").replace("[", "\\[").replace("]", "\\]")
+                copyToClipboard("[%s](%s)" % (title, cn["url"]))
+                alert(screen, "Copied Markdown link")
+
+            elif keyCode == KEY["M"] and CURRENT["line"] > -1 and CURRENT["entries"][CURRENT["line"]].get("url"):
+                try:
+                    alert(screen, "Saved to %s" % os.path.basename(saveNote(CURRENT["entries"][CURRENT["line"]], CURRENT["category"])))
+                except OSError as e:
+                    alert(screen, "Cannot save note: %s" % e.strerror)
+
+            elif keyCode in KEY["l"] and CURRENT["line"] > -1 and CURRENT["entries"][CURRENT["line"]].get("url"):
+                cn = CURRENT["entries"][CURRENT["line"]]
+                alert(screen, "Saving")
+                runInBackground(lambda: saveForLater(cn["url"], cn.get("title", "")) or "Saved for later")
+
+            elif keyCode in KEY["b"] and CURRENT["line"] > -1 and CURRENT["entries"][CURRENT["line"]].get("url"):
+                cn = CURRENT["entries"][CURRENT["line"]]
+
+                if not shareConfigured():
+                    alert(screen, "No Mastodon or Bluesky account set up")
+                    continue
+
+                comment = compose("Share to %s" % SETTINGS["share"]["service"].capitalize(), [cn.get("title", ""), cn["url"], "", "Add a comment, or leave it empty:"])
+
+                drawCategories()
+                drawEntries(clearline=True, force=True)
+                screen.refresh()
+
+                if comment is not None:
+                    alert(screen, "Sharing")
+                    runInBackground(lambda: share(cn["url"], cn.get("title", ""), comment) or "Shared")
+
+            elif keyCode == KEY["@"] and CURRENT["line"] > -1 and CURRENT["entries"][CURRENT["line"]].get("url"):
+                cn = CURRENT["entries"][CURRENT["line"]]
+
+                if not SETTINGS.get("mail"):
+                    alert(screen, "No mail set up")
+                    continue
+
+                to = compose("Mail", [cn.get("title", ""), cn["url"], "", "Send to, or leave it empty for %s:" % SETTINGS["mail"].get("to", "")])
+
+                drawCategories()
+                drawEntries(clearline=True, force=True)
+                screen.refresh()
+
+                if to is not None:
+                    alert(screen, "Sending")
+                    runInBackground(lambda: sendMail(cn, to.strip()) or "Sent")
 
-            elif keyCode == KEY["space"]:
-                cn = data[CURRENT["category"]]["entries"][CURRENT["line"]]
+            elif keyCode in KEY["a"] and CURRENT["line"] > -1 and CURRENT["entries"][CURRENT["line"]].get("enclosure"):
+                cn = CURRENT["entries"][CURRENT["line"]]
+                runCommand(feedOption(CURRENT["category"], cn.get("source", cn.get("sourceName")), "player", "mpv {url}"), cn["enclosure"]["url"])
+                alert(screen, "Playing")
+
+            elif keyCode == KEY["d"] and CURRENT["line"] > -1 and CURRENT["entries"][CURRENT["line"]].get("enclosure"):
+                cn = CURRENT["entries"][CURRENT["line"]]
+                alert(screen, "Queued" if addDownload(cn["enclosure"]["url"], cn.get("title", ""), MESSAGES.put) else "Already downloaded")
+
+            elif keyCode == KEY["D"]:
+                showDownloads()
+                drawCategories()
+                drawEntries(clearline=True, force=True)
+                screen.refresh()
+
+            elif keyCode in KEY["i"] and CURRENT["line"] > -1 and "group" not in CURRENT["entries"][CURRENT["line"]]:
+                showDetail(CURRENT["entries"][CURRENT["line"]])
+                drawCategories()
+                drawEntries(clearline=True, force=True)
+                screen.refresh()
+
+            elif keyCode == KEY["!"] and CURRENT["line"] > -1 and "group" not in CURRENT["entries"][CURRENT["line"]]:
+                showDetail(CURRENT["entries"][CURRENT["line"]], raw=True)
+                drawCategories()
+                drawEntries(clearline=True, force=True)
+                screen.refresh()
+
+            elif keyCode in KEY["x"]:
+                try:
+                    alert(screen, "Exported to %s" % exportToFile(CURRENT["entries"], CURRENT["category"]))
+                except OSError as e:
+                    alert(screen, "Export failed: %s" % e.strerror)
+
+            elif keyCode == KEY["#"]:
+                showStats()
+                drawCategories()
+                drawEntries(clearline=True, force=True)
+                screen.refresh()
+
+            elif keyCode in KEY["e"]:
+                showErrors()
+                drawCategories()
+                drawEntries(clearline=True, force=True)
+                screen.refresh()
+
+            elif keyCode in KEY["p"]:
+                CONFIG["preview"] = not CONFIG["preview"]
+                updateView()
+
+                if CURRENT["line"] >= CONFIG["rowlimit"]:
+                    CURRENT["line"] = -1
+
+                clearScreen()
+                drawCategories()
+                drawEntries(force=True)
+
+            elif keyCode in KEY["u"] + KEY["f"]:
+                if keyCode in KEY["u"]:
+                    CONFIG["hideRead"] = not CONFIG["hideRead"]
+                else:
+                    CONFIG["today"] = not CONFIG["today"]
+
+                CURRENT["line"] = -1
+                CURRENT["offset"] = 0
+                resetListArrowKey()
+                updateView()
+
+                drawCategories()
+                drawEntries(force=True)
+                screen.refresh()
+
+            elif keyCode in KEY["+"] or keyCode == KEY["-"]:
+                interval = refreshInterval(CURRENT["category"])
+                steps = sorted(set(INTERVALS + [interval]))
+                i = steps.index(interval) + (1 if keyCode == KEY["-"] else -1)
+                CONFIG["refreshOverride"][CURRENT["category"]] = steps[min(max(i, 0), len(steps) - 1)]
+
+                interval = refreshInterval(CURRENT["category"])
+                alert(screen, "Refresh every %s" % ("%d min" % (interval // 60) if interval >= 60 else "%d sec" % interval))
+
+            elif keyCode in [KEY["["], KEY["]"]]:
+                hours = timeRange()
+                steps = sorted(set(RANGES + [hours]), key=lambda d: d or float("inf"))
+                i = steps.index(hours) + (1 if keyCode == KEY["]"] else -1)
+                STATE.setdefault("range", {})[CURRENT["category"]] = steps[min(max(i, 0), len(steps) - 1)]
+                saveState()
+
+                CURRENT["line"] = -1
+                CURRENT["offset"] = 0
+                resetListArrowKey()
+                updateView()
+
+                drawCategories()
+                drawEntries(force=True)
+                screen.refresh()
+
+            elif keyCode in KEY["v"]:
+                modes = ["list", "group", "columns"]
+                CONFIG["mode"] = modes[(modes.index(CONFIG["mode"]) + 1) % len(modes)]
+
+                CURRENT["line"] = -1
+                CURRENT["offset"] = 0
+                resetListArrowKey()
+                updateView()
+
+                drawEntries(force=True)
+                screen.refresh()
+
+            elif keyCode == KEY["space"] and -1 < CURRENT["line"] < len(CURRENT["entries"]):
+                cn = CURRENT["entries"][CURRENT["line"]]
 
                 openURL(cn)
 
@@ -559,38 +1944,94 @@ def layout(screen):
                 showCurrentInputNumber()
                 screen.refresh()
 
+            elif keyCode in KEY["g"]:
+                sorts = list(SORTS)
+                sort = STATE.get("sort", {}).get(CURRENT["category"], "newest")
+                STATE.setdefault("sort", {})[CURRENT["category"]] = sorts[(sorts.index(sort) + 1) % len(sorts)]
+                saveState()
+
+                CURRENT["line"] = -1
+                CURRENT["offset"] = 0
+                resetListArrowKey()
+                updateView()
+
+                drawCategories()
+                drawEntries(force=True)
+                screen.refresh()
+
+            elif keyCode in KEY["t"]:
+                names = list(THEMES)
+                setTheme(names[(names.index(CONFIG["theme"]) + 1) % len(names)])
+
+                clearScreen()
+                drawCategories()
+                drawEntries(force=True)
+                alert(screen, "Theme: %s" % CONFIG["theme"])
+
             elif keyCode in KEY["h"] or keyCode == KEY["?"]:
                 showHelp()
                 drawCategories()
                 drawEntries(clearline=True, force=True)
                 screen.refresh()
 
+            elif keyCode == KEY["ctrlT"]:
+                picked = pick("Entry", entryChoices)
+
+                if picked:
+                    selectEntry(*picked)
+
+                drawCategories()
+                drawEntries(force=True)
+                screen.refresh()
+
+            elif keyCode == KEY["ctrlP"]:
+                picked = pick("Go to", feedChoices, widen=False)
+
+                if picked:
+                    switchCategory(picked[0])
+                    CURRENT["source"] = picked[1]
+                    updateView()
+
+                clearScreen()
+                drawCategories()
+                drawEntries(force=True)
+                screen.refresh()
+
+            elif 0 < keyCode < 0x110000 and chr(keyCode) in hookKeys() and CURRENT["line"] > -1:
+                try:
+                    message = hookKeys()[chr(keyCode)](dict(CURRENT["entries"][CURRENT["line"]]))
+                except Exception as e:
+                    message = str(e)
+
+                if message:
+                    alert(screen, message)
+
             elif keyCode in [KEY["tab"], KEY["shiftTab"]]:
                 for idx, d in enumerate(CONFIG["categories"]):
                     if CURRENT["category"] == d[0]:
                         try:
-                            CURRENT["category"] = CONFIG["categories"][idx + (1 if keyCode == KEY["tab"] else -1)][0]
+                            category = CONFIG["categories"][idx + (1 if keyCode == KEY["tab"] else -1)][0]
                         except:
-                            CURRENT["category"] = CONFIG["categories"][0 if keyCode == KEY["tab"] else -1][0]
+                            category = CONFIG["categories"][0 if keyCode == KEY["tab"] else -1][0]
                         break
 
-                drawCategories()
-                alert(screen, "LOADING")
-
-                data[CURRENT["category"]] = getFeed(CURRENT["category"])
-
-                CURRENT["line"] = -1
-                CURRENT["oline"] = -1
-                CONFIG["rowlimit"] = screen.height - 1
-                if CURRENT["category"] in data and len(data[CURRENT["category"]]["entries"]) < CONFIG["rowlimit"]:
-                    CONFIG["rowlimit"] = len(data[CURRENT["category"]]["entries"])
+                switchCategory(category)
 
                 drawCategories()
                 drawEntries(force=True)
+                drawLoading(force=True)
                 screen.refresh()
 
+            if CURRENT["line"] >= CONFIG["rowlimit"]:
+                CURRENT["line"] = -1
+
             if CURRENT["line"] > -1:
-                CURRENT["id"] = data[CURRENT["category"]]["entries"][CURRENT["line"]].get("id", "")
+                CURRENT["id"] = CURRENT["entries"][CURRENT["line"]].get("id", "")
+
+                if scrollTo(CURRENT["line"]):
+                    drawEntries(force=True)
+                    screen.refresh()
+                    continue
 
             if keyCode in KEYLIST["arrow"]:
                 drawEntries(clearline=True)
@@ -602,7 +2043,7 @@ def layout(screen):
             screen.refresh()
             #"""
 
-        if CURRENT["line"] > -1:
+        if CURRENT["line"] > -1 and CONFIG["marquee"]:
             oCurrentTime = currentTime
             currentTime = int(time.time() * (CONFIG["marqueeSpeed" if CURRENT.get("direction", "left") == "left" else "marqueeSpeedReturn"]))
 
@@ -610,23 +2051,167 @@ def layout(screen):
                 doTimer()
 
         if screen.has_resized():
+            rememberPlace()
+            CONFIG["category"] = CURRENT["category"]
             return False
 
 
+def findCategory(name):
+    for key, title in CONFIG["categories"]:
+        if name.lower() in (key.lower(), title.lower()):
+            return key
+
+    sys.exit(f"Unknown category: {name}")
+
+
+def parseArgs():
+
+    parser = argparse.ArgumentParser(prog="rr", description="RSS reader client for CLI")
+    parser.add_argument("--config", metavar="PATH", default=os.environ.get("RREADER_CONFIG"), help="settings file to use instead of config.json, or $RREADER_CONFIG")
+    parser.add_argument("--data-dir", metavar="PATH", default=os.environ.get("RREADER_DATA_DIR"), help="keep feeds, settings and cache in this directory, or $RREADER_DATA_DIR")
+    parser.add_argument("--category", metavar="NAME", help="category tab to start on")
+    parser.add_argument("--no-auto-refresh", action="store_true", help="do not refresh feeds while running")
+    parser.add_argument("--theme", metavar="NAME", help="color theme from the themes directory")
+    parser.add_argument("--accessible", action="store_true", help="screen reader friendly display")
+    parser.add_argument("--colors", choices=["16", "256", "truecolor"], help="color depth of the terminal, detected by default")
+
+    commands = parser.add_subparsers(dest="command", metavar="COMMAND")
+
+    command = commands.add_parser("import", help="import subscriptions from an OPML file")
+    command.add_argument("filename", help="OPML file")
+
+    command = commands.add_parser("sync", help="add subscriptions from a feed service account")
+    command.add_argument("service", choices=["feedbin", "nextcloud"])
+
+    command = commands.add_parser("cache", help="manage fetched feeds")
+    command.add_argument("action", choices=["clean", "rebuild"], help="clean: remove old entries and caches of removed categories, rebuild: fetch every feed again")
+
+    command = commands.add_parser("list", help="print headlines without starting the reader")
+    command.add_argument("--category", metavar="NAME", default=argparse.SUPPRESS, help="only this category")
+    command.add_argument("--format", choices=["plain", "json", "tsv"], default="plain")
+
+    command = commands.add_parser("read", help="print the text of an article")
+    command.add_argument("target", metavar="URL|NUMBER", help="a link, or the number of an entry in the category")
+    command.add_argument("--category", metavar="NAME", default=argparse.SUPPRESS, help="category of the numbered entry, the first one by default")
+    command.add_argument("--pager", action="store_true", help="show it through $PAGER")
+
+    command = commands.add_parser("serve", help="serve each category as an RSS and Atom feed over HTTP")
+    command.add_argument("--host", default="127.0.0.1", help="address to listen on, 0.0.0.0 for other devices")
+    command.add_argument("--port", type=int, default=8765)
+
+    command = commands.add_parser("export", help="write entries to a CSV, TSV or HTML file")
+    command.add_argument("--category", metavar="NAME", default=argparse.SUPPRESS, help="only this category")
+    command.add_argument("--format", choices=["csv", "tsv", "html"], default="csv")
+    command.add_argument("--group", choices=["source", "date"], help="how entries are grouped in HTML")
+    command.add_argument("--output", metavar="FILE", help="file to write instead of the standard output")
+
+    return parser.parse_args()
+
+
 def do():
     def signalHandler(sig, frame):
         sys.exit("Bye")
 
     signal.signal(signal.SIGINT, signalHandler)
 
-    if not os.path.isfile(FEEDS_FILE_NAME):
+    args = parseArgs()
+
+    if args.data_dir or args.config:
+        setPaths(data_dir=args.data_dir, config_file=args.config)
+
+    if not args.data_dir:
+        migrateLegacyFiles()
+
+    # Read again, as the paths or a config.json moved over from ~/.rreader may be new
+
+    loadSettings()
+
+    loadState()
+    setProxy(SETTINGS.get("proxy"))
+
+    if args.command == "import":
+        importOPML(args.filename)
+        return
+
+    if args.command == "cache":
+        (cleanCache if args.action == "clean" else rebuildCache)()
+        sys.stderr.write("".join(d + "\n" for d in BROKEN))
+        return
+
+    if args.command == "sync":
+        (syncFeedbin if args.service == "feedbin" else syncNextcloud)()
+        return
+
+    if not os.path.isfile(p["file_feeds"]):
         sys.stdout.write("Initalizing RSS feeds...\n")
         dummy = getFeedFromRSS(log=True)
 
-    with open(FEEDS_FILE_NAME, "r") as fp:
-        RSS = json.load(fp)
+    RSS = loadJSON(p["file_feeds"])
 
     CONFIG["categories"] = tuple([(key, d["title"]) for key, d in RSS.items()])
+    CONFIG["feeds"] = RSS
+    CONFIG["category"] = CONFIG["categories"][0][0]
+    CONFIG["autoRefresh"] = not args.no_auto_refresh
+
+    # "marquee": false, "expand", or {"speed": 20, "speedReturn": 400, "delay": 40, "delayReturn": 120} in config.json
+
+    marquee = SETTINGS.get("marquee", True)
+    CONFIG["marquee"] = marquee not in (False, "expand")
+    CONFIG["expand"] = marquee == "expand"
+
+    if isinstance(marquee, dict):
+        for key in ("speed", "speedReturn", "delay", "delayReturn"):
+            if key in marquee:
+                CONFIG["marquee" + key[0].upper() + key[1:]] = marquee[key]
+
+    CONFIG["accessible"] = args.accessible or SETTINGS.get("accessible", False)
+
+    if CONFIG["accessible"]:
+        CONFIG["marquee"] = CONFIG["expand"] = False
+
+    CONFIG["color"] = detectColors(args.colors)
+
+    if CONFIG["color"] >= 256:
+        COLOR.update(COLOR256)
+
+    THEMES["default"] = dict(COLOR)
+    THEMES["light"] = LIGHT
+
+    loadThemes()
+
+    theme = args.theme or SETTINGS.get("theme", "default")
+
+    if theme not in THEMES:
+        sys.exit(f"Unknown theme: {theme}")
+
+    setTheme(theme)
+
+    try:
+        loadHooks()
+    except Exception as e:
+        sys.exit(f"Cannot load hooks.py: {e}")
+
+    if args.category:
+        CONFIG["category"] = findCategory(args.category)
+
+    if args.command == "list":
+        listHeadlines([findCategory(args.category)] if args.category else [d[0] for d in CONFIG["categories"]], fmt=args.format, ttl=cacheTTL)
+        sys.stderr.write("".join(d + "\n" for d in BROKEN))
+        return
+
+    if args.command == "read":
+        readArticleText(args.target, CONFIG["category"], pager=args.pager, ttl=cacheTTL)
+        return
+
+    if args.command == "serve":
+        serveFeeds(args.host, args.port, ttl=cacheTTL, interval=refreshInterval)
+        return
+
+    if args.command == "export":
+        if args.group:
+            SETTINGS["exportGroup"] = args.group
+        exportEntries([findCategory(args.category)] if args.category else [d[0] for d in CONFIG["categories"]], fmt=args.format, output=args.output, ttl=cacheTTL)
+        return
 
     while True:
         if Screen.wrapper(layout):
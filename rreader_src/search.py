@@ -0,0 +1,64 @@
+import json
+import os
+import sqlite3
+
+from .common import p
+
+# Every entry ever fetched, kept in index.db in the data dir with a full-text index of titles and content
+
+
+def connect():
+
+    db = sqlite3.connect(os.path.join(p["path_data"], "index.db"), timeout=10)
+    db.execute("CREATE TABLE IF NOT EXISTS entries (id TEXT PRIMARY KEY, category TEXT, timestamp INTEGER, data TEXT)")
+
+    try:
+        db.execute("CREATE VIRTUAL TABLE IF NOT EXISTS fts USING fts5(title, description)")
+    except sqlite3.OperationalError:  # SQLite built without FTS5: search falls back to LIKE
+        pass
+
+    return db
+
+
+def hasFTS(db):
+
+    return db.execute("SELECT 1 FROM sqlite_master WHERE name = 'fts'").fetchone() is not None
+
+
+def add(category, entries):
+
+    db = connect()
+    fts = hasFTS(db)
+
+    with db:
+        for entry in entries:
+            cursor = db.execute("INSERT OR IGNORE INTO entries VALUES (?, ?, ?, ?)", (entry["id"], category, entry["timestamp"], json.dumps(entry, ensure_ascii=False)))
+            if cursor.rowcount and fts:
+                db.execute("INSERT INTO fts (rowid, title, description) VALUES (?, ?, ?)", (cursor.lastrowid, entry.get("title", ""), entry.get("description", "")))
+
+    db.close()
+
+
+def search(query, limit=500):
+
+    # Entries matching every word of the query, as prefixes, best matches first
+
+    words = query.split()
+
+    if not words:
+        return []
+
+    db = connect()
+
+    if hasFTS(db):
+        match = " ".join('"%s"*' % word.replace('"', '""') for word in words)
+        rows = db.execute("SELECT entries.data FROM fts JOIN entries ON entries.rowid = fts.rowid WHERE fts MATCH ? ORDER BY rank LIMIT ?", (match, limit))
+    else:
+        where = " AND ".join(["data LIKE ?"] * len(words))
+        rows = db.execute("SELECT data FROM entries WHERE %s ORDER BY timestamp DESC LIMIT ?" % where, ["%" + word + "%" for word in words] + [limit])
+
+    rslt = [json.loads(row[0]) for row in rows]
+
+    db.close()
+
+    return rslt
@@ -0,0 +1,217 @@
+import datetime
+import email.utils
+import html
+import http.server
+import json
+import os
+import sys
+import threading
+import time
+import urllib.parse
+
+from .common import loadJSON, p, readJSON
+from .config import formatTime
+from .get_rss import do as getFeedFromRSS
+from .state import READ, loadState, markRead
+
+# rr serve: each category as a merged RSS or Atom feed, at /CATEGORY.rss and /CATEGORY.atom,
+# as a web page at /CATEGORY for reading on a phone, and as /CATEGORY.json for readers set up with "server"
+
+# Categories being fetched, whether requested or scheduled; LOCK guards only this set, so requests
+# for other categories, or for a cached copy, never wait behind a fetch
+
+LOCK, FETCHING = threading.Lock(), set()
+
+# and state.json is loaded, changed and saved by one request at a time
+
+STATE_LOCK = threading.Lock()
+
+STYLE = """
+body{font-family:sans-serif;margin:0 auto;max-width:50em;padding:0 .8em;line-height:1.4}
+nav{position:sticky;top:0;background:#fff;padding:.6em 0;border-bottom:1px solid #ddd;overflow-x:auto;white-space:nowrap}
+nav a{margin-right:1em;text-decoration:none;color:#555}nav a.on{color:#000;font-weight:bold}
+li{list-style:none;padding:.5em 0;border-bottom:1px solid #eee}ul{padding:0}
+li a{text-decoration:none;color:#06c}li.read a{color:#999}small{display:block;color:#888}
+"""
+
+
+def page(RSS, category, entries):
+
+    # Read-only list of the category's headlines; entries opened in the reader are dimmed
+
+    esc = html.escape
+
+    nav = "".join('<a href="/%s"%s>%s</a>' % (urllib.parse.quote(key), ' class="on"' if key == category else "", esc(d["title"])) for key, d in RSS.items())
+    items = "".join(
+        '<li%s><a href="%s">%s</a><small>%s &middot; %s</small></li>' % (' class="read"' if entry["id"] in READ else "", esc(entry.get("url", "")), esc(entry.get("title", "")), esc(entry.get("sourceName", "")), esc(formatTime(entry.get("timestamp", 0))))
+        for entry in entries
+    )
+    feeds = '<p><small><a href="/%s.rss">RSS</a> &middot; <a href="/%s.atom">Atom</a></small></p>' % (category, category)
+
+    return '<!DOCTYPE html>\n<html>\n<head>\n<meta charset="utf-8">\n<meta name="viewport" content="width=device-width, initial-scale=1">\n<title>%s - rReader</title>\n<style>%s</style>\n</head>\n<body>\n<nav>%s</nav>\n<ul>%s</ul>\n%s\n</body>\n</html>\n' % (
+        esc(RSS[category]["title"]),
+        STYLE,
+        nav,
+        items,
+        feeds,
+    )
+
+
+def rss(title, link, entries):
+
+    esc = html.escape
+    items = []
+
+    for entry in entries:
+        items.append(
+            "<item><title>%s</title><link>%s</link><guid isPermaLink=\"false\">%s</guid><pubDate>%s</pubDate><author>%s</author><description>%s</description></item>"
+            % (esc(entry.get("title", "")), esc(entry.get("url", "")), esc(entry["id"]), email.utils.formatdate(entry.get("timestamp", 0)), esc(entry.get("sourceName", "")), esc(entry.get("description", "")))
+        )
+
+    return '<?xml version="1.0" encoding="utf-8"?>\n<rss version="2.0"><channel><title>%s</title><link>%s</link><description>%s from rReader</description>%s</channel></rss>\n' % (esc(title), esc(link), esc(title), "".join(items))
+
+
+def atom(title, link, entries):
+
+    esc = html.escape
+    iso = lambda ts: datetime.datetime.fromtimestamp(ts, datetime.timezone.utc).isoformat()
+    items = []
+
+    for entry in entries:
+        items.append(
+            '<entry><title>%s</title><link href="%s"/><id>%s</id><updated>%s</updated><author><name>%s</name></author><summary>%s</summary></entry>'
+            % (esc(entry.get("title", "")), esc(entry.get("url", "")), esc(entry["id"]), iso(entry.get("timestamp", 0)), esc(entry.get("sourceName", "")), esc(entry.get("description", "")))
+        )
+
+    updated = iso(entries[0].get("timestamp", 0) if entries else 0)
+
+    return '<?xml version="1.0" encoding="utf-8"?>\n<feed xmlns="http://www.w3.org/2005/Atom"><title>%s</title><id>%s</id><link rel="self" href="%s"/><updated>%s</updated>%s</feed>\n' % (esc(title), esc(link), esc(link), updated, "".join(items))
+
+
+def getFeed(category, ttl):
+
+    # The cache when it is fresh, or while another thread fetches the category; else fetched here
+
+    while True:
+        d = readJSON(os.path.join(p["path_cache"], f"rss_{category}.json"))
+
+        if d and int(d.get("created_at", 0)) + ttl >= int(time.time()):
+            return d
+
+        with LOCK:
+            busy = category in FETCHING
+            FETCHING.add(category)
+
+        if not busy:
+            break
+
+        if d:
+            return d
+
+        time.sleep(0.2)
+
+    try:
+        return getFeedFromRSS(category)
+    finally:
+        with LOCK:
+            FETCHING.discard(category)
+
+
+def refresher(interval):
+
+    # Keeps every category fresh on one schedule, for all the readers using this server
+
+    while True:
+        for category in loadJSON(p["file_feeds"]):
+            try:
+                getFeed(category, interval(category))
+            except:
+                pass
+
+        time.sleep(10)
+
+
+def do(host="127.0.0.1", port=8765, ttl=lambda category: 120, interval=None):
+    class Handler(http.server.BaseHTTPRequestHandler):
+        def reply(self, body, contentType, status=200):
+            body = body.encode("utf-8")
+            self.send_response(status)
+            self.send_header("Content-Type", contentType + "; charset=utf-8")
+            self.send_header("Content-Length", str(len(body)))
+            self.end_headers()
+            self.wfile.write(body)
+
+        def do_POST(self):
+
+            # /read with a JSON list of entry ids opened in a reader
+
+            if self.path != "/read":
+                return self.reply("Not found\n", "text/plain", 404)
+
+            try:
+                ids = json.loads(self.rfile.read(int(self.headers.get("Content-Length", 0))).decode("utf-8"))
+            except ValueError:
+                return self.reply("Bad request\n", "text/plain", 400)
+
+            if not isinstance(ids, list) or not all(isinstance(id, str) for id in ids):
+                return self.reply("Bad request\n", "text/plain", 400)
+
+            with STATE_LOCK:
+                loadState()
+
+                for id in ids:
+                    markRead(id)
+
+            self.reply("OK\n", "text/plain")
+
+        def do_GET(self):
+
+            # feeds.json is read on every request, so changes show up without a restart
+
+            RSS = loadJSON(p["file_feeds"])
+            path = urllib.parse.unquote(urllib.parse.urlparse(self.path).path).strip("/")
+            category, _, ext = path.rpartition(".")
+
+            if not path and RSS:
+                path = category = list(RSS)[0]
+
+            if path in RSS:
+                category, ext = path, "html"
+
+            if category not in RSS or ext not in ("rss", "atom", "html", "json"):
+                return self.reply("Not found\n", "text/plain", 404)
+
+            link = "http://%s/%s" % (self.headers.get("Host", "%s:%d" % (host, port)), path)
+            d = getFeed(category, ttl(category))
+
+            entries = d["entries"]
+
+            if ext == "json":
+                with STATE_LOCK:
+                    loadState()
+                    read = [entry["id"] for entry in entries if entry["id"] in READ]
+                self.reply(json.dumps(dict(d, read=read), ensure_ascii=False), "application/json")
+            elif ext == "html":
+                with STATE_LOCK:
+                    loadState()
+                    body = page(RSS, category, entries)
+                self.reply(body, "text/html")
+            elif ext == "rss":
+                self.reply(rss(RSS[category]["title"], link, entries), "application/rss+xml")
+            else:
+                self.reply(atom(RSS[category]["title"], link, entries), "application/atom+xml")
+
+        def log_message(self, format, *args):
+            sys.stdout.write("%s - %s\n" % (self.address_string(), format % args))
+
+    server = http.server.ThreadingHTTPServer((host, port), Handler)
+
+    if interval:
+        threading.Thread(target=refresher, args=(interval,), daemon=True).start()
+
+    sys.stdout.write(f"Serving feeds on http://{host}:{port}/\n")
+
+    try:
+        server.serve_forever()
+    except KeyboardInterrupt:
+        pass
@@ -0,0 +1,86 @@
+import datetime
+import json
+import urllib.parse
+
+from .config import SETTINGS
+from .net import fetch
+
+# "share" in config.json, e.g.
+#   {"service": "mastodon", "url": "https://mastodon.social", "access_token": "..."}
+#   {"service": "bluesky", "handle": "me.bsky.social", "password": "app password"}
+
+
+def text(comment, title, url, limit):
+
+    # The comment, then the title and the link; the title is shortened to fit within limit characters
+
+    prefix = comment + "\n\n" if comment else ""
+    room = limit - len(prefix) - len(url) - 1
+
+    if len(title) > room:
+        title = title[: max(room - 1, 0)] + "…"
+
+    return (prefix + title + "\n" + url).strip()
+
+
+def mastodon(d, comment, title, url):
+
+    fetch(
+        d["url"].rstrip("/") + "/api/v1/statuses",
+        data=urllib.parse.urlencode({"status": text(comment, title, url, 500)}).encode("utf-8"),
+        headers={"Authorization": "Bearer " + d["access_token"]},
+    )
+
+
+def bluesky(d, comment, title, url):
+
+    base = d.get("url", "https://bsky.social").rstrip("/")
+    headers = {"Content-Type": "application/json"}
+
+    body, _ = fetch(base + "/xrpc/com.atproto.server.createSession", data=json.dumps({"identifier": d["handle"], "password": d["password"]}).encode("utf-8"), headers=headers)
+    session = json.loads(body)
+
+    post = text(comment, title, url, 300)
+
+    # Links are only clickable when marked as a facet, by their position in UTF-8 bytes
+
+    start = len(post.encode("utf-8")) - len(url.encode("utf-8"))
+
+    record = {
+        "$type": "app.bsky.feed.post",
+        "text": post,
+        "createdAt": datetime.datetime.now(datetime.timezone.utc).isoformat().replace("+00:00", "Z"),
+        "facets": [{"index": {"byteStart": start, "byteEnd": start + len(url.encode("utf-8"))}, "features": [{"$type": "app.bsky.richtext.facet#link", "uri": url}]}],
+    }
+
+    fetch(
+        base + "/xrpc/com.atproto.repo.createRecord",
+        data=json.dumps({"repo": session["did"], "collection": "app.bsky.feed.post", "record": record}).encode("utf-8"),
+        headers=dict(headers, Authorization="Bearer " + session["accessJwt"]),
+    )
+
+
+SERVICES = {"mastodon": mastodon, "bluesky": bluesky}
+
+
+def configured():
+    return (SETTINGS.get("share") or {}).get("service") in SERVICES
+
+
+def post(url, title="", comment=""):
+
+    # Returns an error message, or None when the post was made
+
+    d = SETTINGS.get("share")
+
+    if not configured():
+        return "No Mastodon or Bluesky account set up"
+
+    try:
+        SERVICES[d["service"]](d, comment, title, url)
+    except KeyError as e:
+        return "share needs %s" % e
+    except Exception as e:
+        return "Share failed: %s" % e
+
+    return None
@@ -0,0 +1,105 @@
+import json
+import os
+import socket
+import time
+
+from .common import p, readJSON, writeJSON
+from .config import SETTINGS
+
+# UI state kept between sessions, in state.json in the data dir
+
+STATE = {}
+
+# Ids of the entries that were opened, the most recent 5000 kept in STATE["read"]
+
+READ = set()
+
+
+def loadState():
+
+    STATE.clear()
+
+    STATE.update(readJSON(os.path.join(p["path_data"], "state.json"), {}))
+
+    READ.clear()
+    READ.update(STATE.get("read", []))
+
+    # Entries read on other machines
+
+    if SETTINGS.get("syncDir"):
+        path = os.path.expanduser(SETTINGS["syncDir"])
+        for name in sorted(os.listdir(path)) if os.path.isdir(path) else []:
+            if name.startswith("read-") and name.endswith(".json") and name != syncFile():
+                mergeRead(readPeer(os.path.join(path, name)))
+
+
+def readPeer(path):
+
+    # Another machine's file, mid-sync or with conflict markers, is skipped; setting it aside like readJSON
+    # does would delete it everywhere
+
+    try:
+        with open(path, "r", encoding="utf-8") as fp:
+            ids = json.load(fp)
+    except (OSError, ValueError):
+        return []
+
+    return [id for id in ids if isinstance(id, str)] if isinstance(ids, list) else []
+
+
+def syncFile():
+
+    # Each machine writes only its own file in "syncDir", so Syncthing or git never sees two changes to one file
+
+    return "read-%s.json" % SETTINGS.get("syncName", socket.gethostname())
+
+
+def saveState():
+
+    data = STATE
+
+    # With "server", read marks are the server's and go there instead; those on disk are left as they are,
+    # in case the server shares this data dir
+
+    if SETTINGS.get("server"):
+        data = dict(STATE, read=readJSON(os.path.join(p["path_data"], "state.json"), {}).get("read", []))
+
+    writeJSON(os.path.join(p["path_data"], "state.json"), data)
+
+    if SETTINGS.get("syncDir") and not SETTINGS.get("server"):
+        path = os.path.expanduser(SETTINGS["syncDir"])
+        os.makedirs(path, exist_ok=True)
+        writeJSON(os.path.join(path, syncFile()), STATE.get("read", []))
+
+
+def mergeRead(ids):
+
+    # Ids read in other sessions, kept so that saving the state does not drop them
+
+    ids = [id for id in ids if id not in READ]
+
+    if ids:
+        STATE["read"] = (STATE.get("read", []) + ids)[-5000:]
+        READ.update(ids)
+
+
+def recordOpen(category, source):
+
+    # For the statistics screen: when, in which category and from which source, the most recent 5000
+
+    STATE["opened"] = (STATE.get("opened", []) + [[int(time.time()), category, source]])[-5000:]
+
+    saveState()
+
+
+def markRead(id):
+
+    if id in READ:
+        return
+
+    STATE["read"] = (STATE.get("read", []) + [id])[-5000:]
+
+    READ.clear()
+    READ.update(STATE["read"])
+
+    saveState()
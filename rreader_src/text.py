@@ -0,0 +1,88 @@
+import re
+import unicodedata
+
+from .config import SETTINGS
+
+# Widths in terminal cells, where Chinese, Japanese and Korean characters take two, and wrapping by them
+
+# Tokens that wrapText keeps on one line: a Chinese or Japanese character with any brackets
+# and punctuation around it, a run of spaces, or a word
+
+CJK = "\u3040-\u30ff\u3400-\u4dbf\u4e00-\u9fff\uf900-\ufaff"
+WRAP = re.compile("[「『（【〔〈《“‘]*[%s][、。，．・：；？！）」』】〕〉》”’ー…]*|\\s+|[^\\s%s]+" % (CJK, CJK))
+
+
+def isDoubleChar(s):
+
+    # "ambiguousWidth" of 1 or 2 follows Unicode's East Asian Width, taking characters like ± or ① as
+    # narrow or wide to match the terminal; unset, the ranges below are taken as wide
+
+    if SETTINGS.get("ambiguousWidth") in (1, 2):
+        wide = ("W", "F", "A") if SETTINGS["ambiguousWidth"] == 2 else ("W", "F")
+        return any(unicodedata.east_asian_width(d) in wide for d in s)
+
+    return (
+        re.compile(
+            "(\u00a9|\u00ae|[\u2000-\u3300]|\ud83c[\ud000-\udfff]|\ud83d[\ud000-\udfff]|\ud83e[\ud000-\udfff]|[가-힣]|[\u4e00-\u9fff]|[\u3400-\u4dbf]|[\U00020000-\U0002a6df]|[\U0002a700-\U0002b73f]|[\U0002b740-\U0002b81f]|[\U0002b820-\U0002ceaf])"
+        ).findall(s)
+        != []
+    )
+
+
+def textLength(s):
+
+    return sum([2 if isDoubleChar(d) else 1 for d in s])
+
+
+def wrapText(s, width):
+
+    # Breaks at spaces, and between Chinese and Japanese characters, which have none; longer words are split.
+    # Closing punctuation stays with the text before it, and opening brackets with the text after
+
+    lines = []
+
+    for paragraph in s.split("\n"):
+        line, length = "", 0
+
+        for i, token in enumerate(WRAP.findall(paragraph)):
+            w = textLength(token)
+
+            if token.isspace():
+                if length + w <= width and (line or i == 0):
+                    line += token
+                    length += w
+                continue
+
+            if length + w > width and line:
+                lines.append(line.rstrip())
+                line, length = "", 0
+
+            if w <= width:
+                line += token
+                length += w
+                continue
+
+            for d in token:
+                w = 2 if isDoubleChar(d) else 1
+                if length + w > width:
+                    lines.append(line)
+                    line, length = "", 0
+                line += d
+                length += w
+
+        lines.append(line.rstrip())
+
+    return lines
+
+
+def truncateText(s, width):
+
+    rslt = ""
+
+    for d in s:
+        width -= 2 if isDoubleChar(d) else 1
+        if width < 0:
+            break
+        rslt += d
+
+    return rslt
@@ -0,0 +1,30 @@
+import re
+
+from .discover import findFeedURL
+
+FEED = "https://www.youtube.com/feeds/videos.xml?channel_id=%s"
+
+
+def feedURL(url):
+
+    # youtube:@handle, youtube:UC... or a channel page URL to the channel's feed; None for anything else
+
+    if url.startswith("youtube:"):
+        name = url[8:].strip()
+        if re.match(r"^UC[\w-]{22}$", name):
+            return FEED % name
+        url = "https://www.youtube.com/" + ("" if name.startswith("@") else "@") + name
+
+    m = re.match(r"^https?://(?:www\.|m\.)?youtube\.com/(.+)$", url)
+
+    if not m or m.group(1).startswith("feeds/"):
+        return None
+
+    channel = re.match(r"^channel/(UC[\w-]{22})", m.group(1))
+
+    if channel:
+        return FEED % channel.group(1)
+
+    # Handle, /c/ and /user/ pages link to the channel's feed
+
+    return findFeedURL(url)